@@ -0,0 +1,46 @@
+/// Side effects requested by [`App`](super::App). They are run by the
+/// [`Executor`](super::Executor) so the state machine itself never touches
+/// the network or the terminal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    CancelRequest,
+    SearchMovies,
+    /// Search of the search box while typing, replacing the previous one
+    LiveSearch,
+    NextPage,
+    PreviousPage,
+    /// Page of the current search
+    GoToPage(u32),
+    /// Current page again from YTS instead of the cache
+    RefreshPage,
+    /// Next page into the cache, without showing it
+    PrefetchNextPage,
+    /// Index of the movie in the current page
+    SearchTorrents(usize),
+    /// Index of the movie in the current page
+    SearchSubtitles(usize),
+    /// Index of the movie in the current page
+    FetchDetails(usize),
+    /// Index of the movie in the watchlist
+    WatchlistTorrents(usize),
+    /// Index of the movie in the watchlist
+    WatchlistSubtitles(usize),
+    /// Index of the torrent in the torrents popup
+    AddTorrent(usize),
+    /// Index of the subtitle in the subtitles popup
+    DownloadSubtitle(usize),
+    /// Index of the download in the transmission table
+    ToggleDownload(usize),
+    /// Index of the download in the transmission table
+    RemoveDownload(usize),
+    ScanDownloads,
+    /// Files and subtitles of the download dir, for the status of the movies
+    ScanLibrary,
+    /// Write the search history file
+    SaveHistory,
+    /// Write the watchlist file
+    SaveWatchlist,
+    /// Torrents of the watchlist movies not grabbed yet, by the rules of the monitor
+    CheckWatchlist,
+}
@@ -0,0 +1,178 @@
+use std::{ops::ControlFlow, time::Duration};
+
+use crate::app::{Action, App};
+use crate::tasks::{Background, DownloadChange, Event};
+
+pub struct Executor {
+    background: Background,
+    scanning: bool,
+}
+
+impl Executor {
+    pub fn new() -> Executor {
+        Self {
+            background: Background::new(),
+            scanning: false,
+        }
+    }
+
+    pub fn pending(&self) -> Option<(&'static str, Duration)> {
+        self.background.pending()
+    }
+
+    pub fn try_recv(&mut self) -> Option<Event> {
+        let event = self.background.try_recv();
+        if matches!(event, Some(Event::Downloads(_))) {
+            self.scanning = false;
+        }
+        event
+    }
+
+    pub fn execute(&mut self, app: &App, actions: Vec<Action>) -> ControlFlow<()> {
+        for action in actions {
+            self.run(app, action)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn run(&mut self, app: &App, action: Action) -> ControlFlow<()> {
+        let background = &mut self.background;
+
+        match action {
+            Action::Quit => return ControlFlow::Break(()),
+            Action::CancelRequest => {
+                background.cancel();
+            }
+            Action::SearchMovies => {
+                background.request("Searching movies", app.movie_table.search(), Event::Movies);
+            }
+            Action::LiveSearch => {
+                background.request_quietly(app.movie_table.search(), Event::LiveMovies);
+            }
+            Action::NextPage => {
+                if let Some(task) = app.movie_table.next_page() {
+                    background.request("Getting next page", task, Event::Movies);
+                }
+            }
+            Action::PreviousPage => {
+                if let Some(task) = app.movie_table.previous_page() {
+                    background.request("Getting previous page", task, Event::Movies);
+                }
+            }
+            Action::GoToPage(page) => {
+                if let Some(task) = app.movie_table.go_to_page(page) {
+                    background.request("Getting page", task, Event::Movies);
+                }
+            }
+            Action::RefreshPage => {
+                if app.movie_table.response.page.current > 0 {
+                    background.request("Refreshing page", app.movie_table.refresh(), Event::Movies);
+                }
+            }
+            Action::PrefetchNextPage => {
+                if let Some(task) = app.movie_table.prefetch_next_page() {
+                    background.detach(task);
+                }
+            }
+            Action::SearchTorrents(index) => {
+                if let Some(movie) = app.movie_table.response.movies.get(index) {
+                    background.request(
+                        "Searching torrents",
+                        app.popup_torrent.search_torrents(movie),
+                        Event::Torrents,
+                    );
+                }
+            }
+            Action::SearchSubtitles(index) => {
+                if let Some(movie) = app.movie_table.response.movies.get(index) {
+                    background.request(
+                        "Searching subtitles",
+                        app.popup_subtitle.search_subtitles(movie),
+                        Event::Subtitles,
+                    );
+                }
+            }
+            Action::WatchlistTorrents(index) => {
+                if let Some(entry) = app.watchlist.entries.get(index) {
+                    background.request(
+                        "Searching torrents",
+                        app.popup_torrent.search_torrents(&entry.movie),
+                        Event::Torrents,
+                    );
+                }
+            }
+            Action::WatchlistSubtitles(index) => {
+                if let Some(entry) = app.watchlist.entries.get(index) {
+                    background.request(
+                        "Searching subtitles",
+                        app.popup_subtitle.search_subtitles(&entry.movie),
+                        Event::Subtitles,
+                    );
+                }
+            }
+            Action::FetchDetails(index) => {
+                if let Some(movie) = app.movie_table.response.movies.get(index) {
+                    let link = movie.link.clone();
+                    background.spawn(app.movie_details.fetch(movie), move |result| {
+                        Event::Details(link, result)
+                    });
+                }
+            }
+            Action::AddTorrent(index) => {
+                if let Some(torrent) = app.popup_torrent.torrents.get(index) {
+                    background.spawn(app.transmission.add(&torrent.link), |result| {
+                        Event::DownloadChanged(DownloadChange::Add, result)
+                    });
+                }
+            }
+            Action::DownloadSubtitle(index) => {
+                if let Some(sub) = app.popup_subtitle.subtitles.get(index) {
+                    let movie = sub.movie.clone();
+
+                    background.request(
+                        "Downloading subtitle",
+                        app.popup_subtitle
+                            .download_subtitle(&sub.download_link, &sub.movie),
+                        move |result| Event::SubtitleDownloaded(movie, result),
+                    );
+                }
+            }
+            Action::ToggleDownload(index) => {
+                if index < app.transmission.torrents.len() {
+                    background.spawn(app.transmission.toggle(index), |result| {
+                        Event::DownloadChanged(DownloadChange::Toggle, result)
+                    });
+                }
+            }
+            Action::RemoveDownload(index) => {
+                if index < app.transmission.torrents.len() {
+                    background.spawn(app.transmission.remove(index), |result| {
+                        Event::DownloadChanged(DownloadChange::Remove, result)
+                    });
+                }
+            }
+            Action::ScanDownloads => {
+                if !self.scanning && app.transmission.should_scan() {
+                    background.spawn(app.transmission.scan(), Event::Downloads);
+                    self.scanning = true;
+                }
+            }
+            Action::ScanLibrary => {
+                background.spawn(app.library.scan(), |(files, subtitles)| {
+                    Event::LibraryScanned(files, subtitles)
+                });
+            }
+            Action::SaveHistory => {
+                background.spawn(app.history.save(), Event::HistorySaved);
+            }
+            Action::SaveWatchlist => {
+                background.spawn(app.watchlist.save(), Event::WatchlistSaved);
+            }
+            Action::CheckWatchlist => {
+                background.spawn(app.monitor.check(), Event::WatchlistChecked);
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+}
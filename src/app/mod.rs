@@ -0,0 +1,450 @@
+mod action;
+mod executor;
+mod state;
+
+pub use action::*;
+pub use executor::*;
+pub use state::*;
+
+use std::io;
+
+use crossterm::event::{
+    self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+};
+use crossterm::execute;
+use ratatui::layout::{Constraint, Layout, Margin, Rect};
+use ratatui::widgets::Clear;
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::config::configuration;
+use crate::elements::{Focus, hint_bar};
+
+pub async fn run() -> anyhow::Result<()> {
+    let config = configuration()?;
+
+    color_eyre::install().map_err(anyhow::Error::msg)?;
+
+    let mut app = App::new(&config)?;
+
+    if let Err(e) = app.history.load() {
+        app.notify_error(format!("Error loading search history {e}"));
+    }
+    if let Err(e) = app.watchlist.load() {
+        app.notify_error(format!("Error loading watchlist {e}"));
+    }
+    let mut executor = Executor::new();
+
+    let _ = executor.execute(&app, vec![Action::ScanDownloads, Action::ScanLibrary]);
+
+    // ratatui::init installs a panic hook which restores the terminal before
+    // handing the panic to color_eyre
+    let mut terminal = ratatui::init();
+
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = execute!(io::stdout(), DisableMouseCapture, DisableBracketedPaste);
+        hook(info);
+    }));
+
+    let result = execute!(io::stdout(), EnableMouseCapture, EnableBracketedPaste)
+        .map_err(anyhow::Error::from)
+        .and_then(|_| event_loop(&mut terminal, &mut app, &mut executor));
+
+    let _ = execute!(io::stdout(), DisableMouseCapture, DisableBracketedPaste);
+    ratatui::restore();
+
+    result
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+    executor: &mut Executor,
+) -> anyhow::Result<()> {
+    let mut last_redraw_time = tokio::time::Instant::now();
+    let redraw_interval = tokio::time::Duration::from_secs(1);
+    let tick_interval = tokio::time::Duration::from_millis(100);
+
+    loop {
+        while let Some(event) = executor.try_recv() {
+            let actions = app.handle_event(event);
+            let _ = executor.execute(app, actions);
+        }
+
+        let actions = app.tick(std::time::Instant::now());
+        let _ = executor.execute(app, actions);
+
+        app.popup_loading.update(executor.pending(), app.theme);
+
+        terminal.draw(|frame| render(frame, app))?;
+
+        // Kitty and sixel posters go over the cells of the frame
+        if app.movie_details.poster.flush(&mut io::stdout())? {
+            terminal.clear()?;
+        }
+
+        if tokio::time::Instant::now().duration_since(last_redraw_time) >= redraw_interval {
+            let _ = executor.execute(app, vec![Action::ScanDownloads]);
+            last_redraw_time = tokio::time::Instant::now();
+        }
+
+        let time_since_last_redraw = tokio::time::Instant::now().duration_since(last_redraw_time);
+        let timeout = redraw_interval
+            .saturating_sub(time_since_last_redraw)
+            .min(tick_interval);
+
+        if event::poll(timeout)? {
+            let actions = match event::read()? {
+                event::Event::Mouse(mouse) => app.handle_mouse(mouse),
+                event::Event::Paste(text) => app.handle_paste(text),
+                event => match event.as_key_press_event() {
+                    Some(key) => app.handle_key(key),
+                    None => vec![],
+                },
+            };
+
+            if executor.execute(app, actions).is_break() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Terminal width from which movies and downloads are shown side by side
+const SIDE_BY_SIDE_WIDTH: u16 = 160;
+
+/// Width of the movies panel from which the details are shown beside the table instead of below
+const DETAILS_BESIDE_WIDTH: u16 = 110;
+const DETAILS_HEIGHT: u16 = 12;
+
+fn render(frame: &mut Frame, app: &mut App) {
+    let hints = hint_bar(app.keymap, app.context(), app.theme);
+
+    let App {
+        keymap,
+        theme,
+        focus,
+        input_box,
+        completion,
+        movie_table,
+        library,
+        movie_details,
+        popup_torrent,
+        popup_subtitle,
+        popup_notification,
+        popup_loading,
+        popup_help,
+        popup_genre,
+        popup_page,
+        watchlist,
+        popup_note,
+        transmission,
+        areas,
+        ..
+    } = app;
+
+    let area = frame.area();
+    let visible = transmission.is_visible();
+
+    // Wide screens show movies and downloads together
+    let side_by_side = visible && area.width >= SIDE_BY_SIDE_WIDTH;
+    let (movies_width, downloads_width) = if side_by_side {
+        (area.width * 3 / 5, area.width - area.width * 3 / 5)
+    } else {
+        (area.width, area.width)
+    };
+
+    // The watchlist takes the place of the movies and their details
+    let show_details = movie_details.show && !watchlist.show;
+    let selected_movie = movie_table
+        .table_state
+        .selected()
+        .and_then(|selected| movie_table.response.movies.get(selected))
+        .cloned();
+    let details_beside = show_details && movies_width >= DETAILS_BESIDE_WIDTH;
+    let table_width = if details_beside {
+        movies_width * 3 / 5
+    } else {
+        movies_width
+    };
+
+    let mut movie_table_state = if watchlist.show {
+        watchlist.table_state
+    } else {
+        movie_table.table_state
+    };
+    let (table, table_height) = if watchlist.show {
+        watchlist.render(focus, theme, table_width)
+    } else {
+        movie_table.render(focus, theme, table_width, library)
+    };
+
+    let constraint = if details_beside {
+        table_height.max(DETAILS_HEIGHT)
+    } else if show_details {
+        table_height + DETAILS_HEIGHT
+    } else {
+        table_height
+    };
+
+    let status = transmission.status(theme);
+    let mut transmission_table_state = transmission.table_state;
+    let (torrent_table, torrent_constraint) = transmission.render(focus, theme, downloads_width);
+
+    let body_height = if side_by_side {
+        constraint.max(torrent_constraint)
+    } else {
+        constraint + torrent_constraint
+    };
+
+    let layout = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(body_height),
+        Constraint::Fill(1),
+        Constraint::Length(1),
+    ]);
+
+    let [input_box_area, body_area, _, hint_bar_area] = area.layout(&layout);
+
+    let [movies_area, torrent_table_area] = if side_by_side {
+        body_area.layout(&Layout::horizontal([
+            Constraint::Length(movies_width),
+            Constraint::Fill(1),
+        ]))
+    } else {
+        body_area.layout(&Layout::vertical([
+            Constraint::Length(constraint),
+            Constraint::Length(torrent_constraint),
+        ]))
+    };
+
+    let [movie_table_area, details_area] = if details_beside {
+        movies_area.layout(&Layout::horizontal([
+            Constraint::Length(table_width),
+            Constraint::Fill(1),
+        ]))
+    } else if show_details {
+        movies_area.layout(&Layout::vertical([
+            Constraint::Length(table_height),
+            Constraint::Fill(1),
+        ]))
+    } else {
+        [movies_area, Rect::default()]
+    };
+
+    // Popups are centered over the movies, or over both tables when side by side
+    let popup_bounds = if side_by_side { body_area } else { movies_area };
+
+    *areas = Areas {
+        input_box: input_box_area,
+        movie_table: movie_table_area,
+        downloads: if visible {
+            torrent_table_area
+        } else {
+            Rect::default()
+        },
+        popup: Rect::default(),
+    };
+
+    frame.render_widget(
+        input_box.render(focus, theme, input_box_area.width),
+        input_box_area,
+    );
+
+    let status_width = (status.width() as u16).min(input_box_area.width.saturating_sub(2));
+    frame.render_widget(
+        status,
+        Rect::new(
+            input_box_area.right().saturating_sub(status_width + 1),
+            input_box_area.y,
+            status_width,
+            1,
+        ),
+    );
+
+    if matches!(focus, Focus::InputBox) {
+        frame.set_cursor_position((
+            input_box_area.x + input_box.cursor_column() + 1,
+            input_box_area.y + 1,
+        ));
+    }
+
+    frame.render_stateful_widget(table, movie_table_area, &mut movie_table_state);
+    // The offset of the rendered rows is kept to find the row under the mouse
+    if watchlist.show {
+        watchlist.table_state = movie_table_state;
+    } else {
+        movie_table.table_state = movie_table_state;
+    }
+
+    if show_details {
+        let inner = details_area.inner(Margin::new(1, 1));
+        let poster = selected_movie
+            .as_ref()
+            .and_then(|movie| Some((movie, movie_details.poster_image(movie)?)));
+        let poster_width = match poster {
+            Some(_) => movie_details.poster_width(inner),
+            None => 0,
+        };
+
+        frame.render_widget(
+            movie_details.render(selected_movie.as_ref(), theme, poster_width),
+            details_area,
+        );
+
+        if let Some((movie, image)) = poster
+            && poster_width > 0
+        {
+            // Images would cover the popups, so they are drawn with half blocks meanwhile
+            let images = !(popup_notification.show
+                || popup_loading.show
+                || popup_torrent.popup.show
+                || popup_subtitle.popup.show
+                || popup_genre.popup.show
+                || popup_page.show
+                || popup_note.show
+                || popup_help.show
+                || completion.is_open());
+
+            let poster_area = Rect {
+                width: poster_width,
+                ..inner
+            };
+            match movie_details
+                .poster
+                .render(&movie.link, &image, poster_area, images)
+            {
+                Some(half_blocks) => frame.render_widget(half_blocks, poster_area),
+                None => frame.render_widget(Clear, poster_area),
+            }
+        }
+    }
+
+    // The downloads go before the popups, which are drawn over them when side by side
+    if visible {
+        frame.render_stateful_widget(
+            torrent_table,
+            torrent_table_area,
+            &mut transmission_table_state,
+        );
+        transmission.table_state = transmission_table_state;
+
+        let torrents_len = transmission.torrents.len();
+
+        if torrents_len > 5 {
+            let mut scroll_state = transmission.scroll_state.content_length(torrents_len + 2);
+
+            frame.render_stateful_widget(theme.scrollbar(), torrent_table_area, &mut scroll_state);
+        }
+    }
+
+    if popup_notification.show {
+        let popup_area = popup_notification.area(popup_bounds);
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(popup_notification.render(theme), popup_area);
+    }
+
+    frame.render_widget(hints, hint_bar_area);
+
+    if popup_loading.show {
+        let popup_area = popup_loading.area(area);
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(popup_loading.render(theme), popup_area);
+    }
+
+    if popup_torrent.popup.show {
+        let popup_area = popup_torrent.area(popup_bounds);
+        let mut table_state = popup_torrent.popup.table_state;
+        frame.render_widget(Clear, popup_area);
+        frame.render_stateful_widget(
+            popup_torrent.render(theme, popup_area.width),
+            popup_area,
+            &mut table_state,
+        );
+        popup_torrent.popup.table_state = table_state;
+        areas.popup = popup_area;
+
+        let mut scroll_state = popup_torrent
+            .popup
+            .scroll_state
+            .content_length(popup_torrent.torrents.len() + 2);
+
+        frame.render_stateful_widget(theme.scrollbar(), popup_area, &mut scroll_state);
+    }
+
+    if popup_subtitle.popup.show {
+        let popup_area = popup_subtitle.area(popup_bounds, popup_bounds.height);
+        let mut table_state = popup_subtitle.popup.table_state;
+        frame.render_widget(Clear, popup_area);
+        frame.render_stateful_widget(
+            popup_subtitle.render(theme, popup_area.width),
+            popup_area,
+            &mut table_state,
+        );
+        popup_subtitle.popup.table_state = table_state;
+        areas.popup = popup_area;
+
+        let len = popup_subtitle.subtitles.len();
+
+        if len > 0 {
+            let mut scroll_state = popup_subtitle.popup.scroll_state.content_length(len + 2);
+
+            frame.render_stateful_widget(theme.scrollbar(), popup_area, &mut scroll_state);
+        }
+    }
+
+    if popup_genre.popup.show {
+        let popup_area = popup_genre.area(popup_bounds);
+        let mut table_state = popup_genre.popup.table_state;
+        frame.render_widget(Clear, popup_area);
+        frame.render_stateful_widget(popup_genre.render(theme), popup_area, &mut table_state);
+        popup_genre.popup.table_state = table_state;
+        areas.popup = popup_area;
+
+        let mut scroll_state = popup_genre
+            .popup
+            .scroll_state
+            .content_length(popup_genre.len() + 2);
+
+        frame.render_stateful_widget(theme.scrollbar(), popup_area, &mut scroll_state);
+    }
+
+    if popup_page.show {
+        let popup_area = popup_page.area(popup_bounds);
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(popup_page.render(theme), popup_area);
+        frame.set_cursor_position((popup_area.x + popup_page.cursor_column(), popup_area.y + 1));
+        areas.popup = popup_area;
+    }
+
+    if popup_note.show {
+        let popup_area = popup_note.area(popup_bounds);
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(popup_note.render(theme, popup_area.width), popup_area);
+        frame.set_cursor_position((
+            popup_area.x + popup_note.cursor_column(popup_area.width),
+            popup_area.y + 1,
+        ));
+        areas.popup = popup_area;
+    }
+
+    if completion.is_open() {
+        let popup_area = completion.area(
+            area,
+            input_box_area.x + input_box.column(completion.start),
+            input_box_area.bottom(),
+        );
+        frame.render_widget(Clear, popup_area);
+        frame.render_stateful_widget(
+            completion.render(theme),
+            popup_area,
+            &mut completion.table_state,
+        );
+    }
+
+    if popup_help.show {
+        let popup_area = popup_help.area(area, keymap);
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(popup_help.render(keymap, theme), popup_area);
+    }
+}
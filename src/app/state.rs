@@ -0,0 +1,1340 @@
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+
+use crate::app::Action;
+use crate::config::{Config, KeyAction, KeyChord, KeyContext, KeyMap, Theme};
+use crate::downloads::Transmission;
+use crate::elements::{
+    Completion, Focus, History, InputBox, Library, MovieDetails, MovieTable, PopupGenre, PopupHelp,
+    PopupLoading, PopupNote, PopupNotification, PopupPage, PopupSubtitle, PopupTorrent, Query,
+    Watchlist, common_prefix, completions, with_filter,
+};
+use crate::tasks::{Check, DownloadChange, Event, Monitor};
+
+/// Areas of the last render, used to know what is under the mouse
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Areas {
+    pub input_box: Rect,
+    pub movie_table: Rect,
+    pub downloads: Rect,
+    pub popup: Rect,
+}
+
+impl Areas {
+    /// Row of a table with borders and a header, without the table offset
+    fn row(area: Rect, position: Position) -> Option<usize> {
+        let first_row = area.y + 2;
+
+        (area.contains(position) && position.y >= first_row && position.y < area.bottom() - 1)
+            .then(|| (position.y - first_row) as usize)
+    }
+}
+
+pub struct App<'a> {
+    pub keymap: &'a KeyMap,
+    pub theme: &'a Theme,
+    pub focus: Focus,
+    pub input_box: InputBox,
+    pub completion: Completion,
+    pub history: History,
+    pub movie_table: MovieTable,
+    /// Downloads and files, for the status column of the movies
+    pub library: Library,
+    pub movie_details: MovieDetails,
+    pub popup_torrent: PopupTorrent<'a>,
+    pub popup_subtitle: PopupSubtitle<'a>,
+    pub popup_notification: PopupNotification,
+    pub popup_loading: PopupLoading,
+    pub popup_help: PopupHelp,
+    pub popup_genre: PopupGenre<'a>,
+    pub popup_page: PopupPage,
+    pub watchlist: Watchlist,
+    pub popup_note: PopupNote,
+    pub transmission: Transmission,
+    pub monitor: Monitor,
+    pub areas: Areas,
+    last_click: Option<(Instant, Position)>,
+    /// Pause of typing before a live search, None when live search is off
+    live_search_delay: Option<Duration>,
+    live_search_at: Option<Instant>,
+    /// Time between checks of the watchlist, None when the monitor is off
+    watch_interval: Option<Duration>,
+    watch_at: Option<Instant>,
+}
+
+impl<'a> App<'a> {
+    pub fn new(config: &'a Config) -> anyhow::Result<App<'a>> {
+        Ok(Self {
+            keymap: &config.keys,
+            theme: &config.theme,
+            focus: Focus::default(),
+            input_box: InputBox::default(),
+            completion: Completion::default(),
+            history: History::new(config.history_path.clone(), config.history_size),
+            movie_table: MovieTable::new(
+                &config.yts_host,
+                config.yts_order.clone(),
+                config.page_cache(),
+            ),
+            library: Library::new(&config.yts_download_dir),
+            movie_details: MovieDetails::new(
+                &config.yts_host,
+                config.poster_dir.clone(),
+                config.posters,
+            ),
+            popup_torrent: PopupTorrent::new(&config.yts_host),
+            popup_subtitle: PopupSubtitle::new(
+                &config.opensubs_langs,
+                config.opensubs_order.clone(),
+                &config.yts_download_dir,
+            ),
+            popup_notification: PopupNotification::new(),
+            popup_loading: PopupLoading::new(),
+            popup_help: PopupHelp::new(),
+            popup_genre: PopupGenre::new(),
+            popup_page: PopupPage::new(),
+            watchlist: Watchlist::new(config.watchlist_path.clone()),
+            popup_note: PopupNote::new(),
+            transmission: Transmission::new(
+                config.transmission_host.clone(),
+                config.transmission_username.clone(),
+                config.transmission_password.clone(),
+                config.yts_download_dir.clone(),
+            )?,
+            monitor: Monitor::new(config)?,
+            areas: Areas::default(),
+            last_click: None,
+            live_search_delay: config.live_search.then_some(config.live_search_delay),
+            live_search_at: None,
+            watch_interval: config.watch_auto.then_some(config.watch_interval),
+            watch_at: None,
+        })
+    }
+
+    /// Context of the keys, popups without focus (loading and help) come first
+    pub fn context(&self) -> KeyContext {
+        if self.popup_loading.show {
+            KeyContext::Loading
+        } else if self.popup_help.show {
+            KeyContext::Help
+        } else if self.history.is_searching() && self.focus == Focus::InputBox {
+            KeyContext::History
+        } else if self.completion.is_open() && self.focus == Focus::InputBox {
+            KeyContext::Completion
+        } else {
+            KeyContext::from(&self.focus)
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Vec<Action> {
+        let text = self.input_box.text().to_string();
+        let actions = self.press(key);
+        self.schedule_live_search(&text);
+        actions
+    }
+
+    fn press(&mut self, key: KeyEvent) -> Vec<Action> {
+        let context = self.context();
+
+        let Some(action) = self.keymap.action(context, key) else {
+            if let KeyCode::Char(c) = key.code
+                && KeyChord::from(key).is_text()
+            {
+                match context {
+                    KeyContext::Search => self.input_box.insert(c),
+                    KeyContext::History => {
+                        let found = self.history.search_push(c);
+                        self.show_history_search(found);
+                    }
+                    KeyContext::Completion => {
+                        self.input_box.insert(c);
+                        self.refine_completion();
+                    }
+                    KeyContext::Page => self.popup_page.push(c),
+                    KeyContext::Note => self.popup_note.input.insert(c),
+                    _ => {}
+                }
+            }
+            return vec![];
+        };
+
+        self.dispatch(context, action)
+    }
+
+    fn dispatch(&mut self, context: KeyContext, action: KeyAction) -> Vec<Action> {
+        match (context, action) {
+            (KeyContext::Loading, KeyAction::Cancel) => return vec![Action::CancelRequest],
+            (_, KeyAction::Quit) => return vec![Action::Quit],
+            (KeyContext::Help, KeyAction::Close) => self.popup_help.show = false,
+            (_, KeyAction::Help) => self.popup_help.open(context),
+            (KeyContext::Search, KeyAction::Search) => return self.search_movies(),
+            (KeyContext::Search, KeyAction::Complete) => {
+                let (start, word) = self.input_box.word_before_cursor();
+                let items = completions(word);
+
+                match items.as_slice() {
+                    [] => return self.dispatch(context, KeyAction::FocusNext),
+                    [item] => self.input_box.complete(start, item),
+                    _ => {
+                        // Like a shell, the shared start is completed before choosing
+                        let prefix = common_prefix(&items);
+                        if prefix.len() > word.len() {
+                            let prefix = prefix.to_string();
+                            self.input_box.complete(start, &prefix);
+                        }
+                        self.completion.open(start, items);
+                    }
+                }
+            }
+            (KeyContext::Completion, KeyAction::Complete | KeyAction::Down) => {
+                self.completion.next()
+            }
+            (KeyContext::Completion, KeyAction::Up) => self.completion.previous(),
+            (KeyContext::Completion, KeyAction::Select) => {
+                if let Some(item) = self.completion.selected() {
+                    let item = item.to_string();
+                    self.input_box.complete(self.completion.start, &item);
+                }
+                self.completion.close();
+            }
+            (KeyContext::Completion, KeyAction::DeleteChar) => {
+                self.input_box.delete_char();
+                self.refine_completion();
+            }
+            (KeyContext::Completion, KeyAction::Close) => self.completion.close(),
+            (KeyContext::Search, KeyAction::DeleteChar) => self.input_box.delete_char(),
+            (KeyContext::Search, KeyAction::DeleteNextChar) => self.input_box.delete_next_char(),
+            (KeyContext::Search, KeyAction::DeleteWord) => self.input_box.delete_word(),
+            (KeyContext::Search, KeyAction::DeleteLine) => self.input_box.delete_to_start(),
+            (KeyContext::Search, KeyAction::CursorLeft) => self.input_box.move_left(),
+            (KeyContext::Search, KeyAction::CursorRight) => self.input_box.move_right(),
+            (KeyContext::Search, KeyAction::LineStart) => self.input_box.move_start(),
+            (KeyContext::Search, KeyAction::LineEnd) => self.input_box.move_end(),
+            (KeyContext::Search, KeyAction::HistoryPrevious) => {
+                if let Some(text) = self.history.previous(self.input_box.text()) {
+                    self.input_box.set_text(text);
+                }
+            }
+            (KeyContext::Search, KeyAction::HistoryNext) => {
+                if let Some(text) = self.history.next() {
+                    self.input_box.set_text(text);
+                }
+            }
+            (KeyContext::Search, KeyAction::HistorySearch) => {
+                self.history.start_search(self.input_box.text());
+                self.input_box.prompt = self.history.prompt();
+            }
+            (KeyContext::History, KeyAction::HistorySearch) => {
+                let found = self.history.search_next();
+                self.show_history_search(found);
+            }
+            (KeyContext::History, KeyAction::DeleteChar) => {
+                let found = self.history.search_pop();
+                self.show_history_search(found);
+            }
+            (KeyContext::History, KeyAction::Select) => {
+                self.history.accept_search();
+                self.input_box.prompt = None;
+                return self.search_movies();
+            }
+            (KeyContext::History, KeyAction::Cancel) => {
+                let draft = self.history.cancel_search();
+                self.input_box.set_text(draft);
+                self.input_box.prompt = None;
+            }
+            (KeyContext::Search, KeyAction::FocusNext) => self.focus = self.table_focus(),
+            (KeyContext::Movies | KeyContext::Watchlist, KeyAction::FocusNext) => {
+                self.focus = if self.transmission.is_visible() {
+                    Focus::TorrentTable
+                } else {
+                    Focus::InputBox
+                };
+            }
+            (KeyContext::Downloads, KeyAction::FocusNext) => self.focus = Focus::InputBox,
+            (KeyContext::Movies, KeyAction::Down) => self.movie_table.table_state.select_next(),
+            (KeyContext::Movies, KeyAction::Up) => self.movie_table.table_state.select_previous(),
+            (KeyContext::Movies, KeyAction::First) => self.movie_table.table_state.select_first(),
+            (KeyContext::Movies, KeyAction::Last) => self.movie_table.table_state.select_last(),
+            (KeyContext::Movies, KeyAction::NextPage) => return vec![Action::NextPage],
+            (KeyContext::Movies, KeyAction::PreviousPage) => return vec![Action::PreviousPage],
+            (KeyContext::Movies, KeyAction::Refresh) => return vec![Action::RefreshPage],
+            (KeyContext::Movies, KeyAction::FirstPage) => return self.go_to_page(1),
+            (KeyContext::Movies, KeyAction::LastPage) => {
+                return self.go_to_page(self.movie_table.response.page.of);
+            }
+            (KeyContext::Movies, KeyAction::GoToPage) if self.movie_table.response.page.of > 1 => {
+                self.popup_page.open(self.movie_table.response.page.of);
+                self.focus = Focus::PopupPage;
+            }
+            (KeyContext::Page, KeyAction::DeleteChar) => self.popup_page.pop(),
+            (KeyContext::Page, KeyAction::Select) => {
+                if let Some(page) = self.popup_page.page() {
+                    self.popup_page.show = false;
+                    self.focus = Focus::MovieTable;
+                    return self.go_to_page(page);
+                }
+            }
+            (KeyContext::Page, KeyAction::Close) => {
+                self.popup_page.show = false;
+                self.focus = Focus::MovieTable;
+            }
+            (KeyContext::Movies, KeyAction::Torrents) => {
+                if let Some(selected) = self.selected_movie() {
+                    return vec![Action::SearchTorrents(selected)];
+                }
+            }
+            (KeyContext::Movies, KeyAction::Watch) => {
+                if let Some(selected) = self.selected_movie() {
+                    let movie = &self.movie_table.response.movies[selected];
+                    let name = movie.name.clone();
+
+                    if self.watchlist.add(movie) {
+                        self.notify_success(format!("{name} added to the watchlist"));
+                        return vec![Action::SaveWatchlist];
+                    }
+                    self.notify(format!("{name} is already in the watchlist"));
+                }
+            }
+            (KeyContext::Movies, KeyAction::Watchlist) => {
+                self.watchlist.show = true;
+                self.focus = Focus::Watchlist;
+            }
+            (KeyContext::Watchlist, KeyAction::Close) => {
+                self.watchlist.show = false;
+                self.focus = Focus::MovieTable;
+            }
+            (KeyContext::Watchlist, KeyAction::Down) => self.watchlist.table_state.select_next(),
+            (KeyContext::Watchlist, KeyAction::Up) => self.watchlist.table_state.select_previous(),
+            (KeyContext::Watchlist, KeyAction::First) => self.watchlist.table_state.select_first(),
+            (KeyContext::Watchlist, KeyAction::Last) => self.watchlist.table_state.select_last(),
+            (KeyContext::Watchlist, KeyAction::Torrents) => {
+                if let Some(selected) = self.watchlist.selected() {
+                    return vec![Action::WatchlistTorrents(selected)];
+                }
+            }
+            (KeyContext::Watchlist, KeyAction::Subtitles) => {
+                if let Some(selected) = self.watchlist.selected() {
+                    return vec![Action::WatchlistSubtitles(selected)];
+                }
+            }
+            (KeyContext::Watchlist, KeyAction::Remove) => {
+                if let Some(selected) = self.watchlist.selected() {
+                    self.watchlist.remove(selected);
+                    return vec![Action::SaveWatchlist];
+                }
+            }
+            (KeyContext::Watchlist, KeyAction::Note) => {
+                if let Some(selected) = self.watchlist.selected() {
+                    self.popup_note
+                        .open(selected, &self.watchlist.entries[selected].note);
+                    self.focus = Focus::PopupNote;
+                }
+            }
+            (KeyContext::Note, KeyAction::DeleteChar) => self.popup_note.input.delete_char(),
+            (KeyContext::Note, KeyAction::DeleteNextChar) => {
+                self.popup_note.input.delete_next_char()
+            }
+            (KeyContext::Note, KeyAction::DeleteWord) => self.popup_note.input.delete_word(),
+            (KeyContext::Note, KeyAction::DeleteLine) => self.popup_note.input.delete_to_start(),
+            (KeyContext::Note, KeyAction::CursorLeft) => self.popup_note.input.move_left(),
+            (KeyContext::Note, KeyAction::CursorRight) => self.popup_note.input.move_right(),
+            (KeyContext::Note, KeyAction::LineStart) => self.popup_note.input.move_start(),
+            (KeyContext::Note, KeyAction::LineEnd) => self.popup_note.input.move_end(),
+            (KeyContext::Note, KeyAction::Select) => {
+                self.popup_note.show = false;
+                self.focus = Focus::Watchlist;
+
+                if let Some(entry) = self.watchlist.entries.get_mut(self.popup_note.index) {
+                    entry.note = self.popup_note.input.text().trim().to_string();
+                    return vec![Action::SaveWatchlist];
+                }
+            }
+            (KeyContext::Note, KeyAction::Close) => {
+                self.popup_note.show = false;
+                self.focus = Focus::Watchlist;
+            }
+            (KeyContext::Movies, KeyAction::Sort) => self.movie_table.sort_next(),
+            (KeyContext::Torrents, KeyAction::Sort) => self.popup_torrent.sort_next(),
+            (KeyContext::Subtitles, KeyAction::Sort) => self.popup_subtitle.sort_next(),
+            (KeyContext::Movies, KeyAction::Details) => {
+                self.movie_details.show = !self.movie_details.show
+            }
+            (KeyContext::Movies, KeyAction::Subtitles) => {
+                if let Some(selected) = self.selected_movie() {
+                    return vec![Action::SearchSubtitles(selected)];
+                }
+            }
+            (KeyContext::Downloads, KeyAction::Down) => {
+                self.transmission.table_state.select_next();
+                self.transmission.scroll_bar_up();
+            }
+            (KeyContext::Downloads, KeyAction::Up) => {
+                self.transmission.table_state.select_previous();
+                self.transmission.scroll_bar_down();
+            }
+            (KeyContext::Downloads, KeyAction::Toggle) => {
+                if let Some(selected) = self.selected_download() {
+                    return vec![Action::ToggleDownload(selected)];
+                }
+            }
+            (KeyContext::Downloads, KeyAction::Remove) => {
+                if let Some(selected) = self.selected_download() {
+                    return vec![Action::RemoveDownload(selected)];
+                }
+            }
+            (KeyContext::Torrents, KeyAction::Down) => {
+                self.popup_torrent.popup.table_state.select_next();
+                self.popup_torrent
+                    .popup
+                    .scroll_bar_down(self.popup_torrent.torrents.len());
+            }
+            (KeyContext::Torrents, KeyAction::Up) => {
+                self.popup_torrent.popup.table_state.select_previous();
+                self.popup_torrent.popup.scroll_bar_up();
+            }
+            (KeyContext::Torrents, KeyAction::Close) => {
+                self.popup_torrent.popup.show = false;
+                self.focus = self.table_focus();
+            }
+            (KeyContext::Torrents, KeyAction::Select) => {
+                self.popup_torrent.popup.show = false;
+                self.focus = self.table_focus();
+
+                if !self.transmission.is_connected() {
+                    self.notify_error(String::from("Transmission is not connected"));
+                } else if let Some(selected) = self.popup_torrent.popup.table_state.selected()
+                    && selected < self.popup_torrent.torrents.len()
+                {
+                    return vec![Action::AddTorrent(selected)];
+                }
+            }
+            (KeyContext::Subtitles, KeyAction::Down) => {
+                self.popup_subtitle.popup.table_state.select_next();
+                self.popup_subtitle
+                    .popup
+                    .scroll_bar_down(self.popup_subtitle.subtitles.len());
+            }
+            (KeyContext::Subtitles, KeyAction::Up) => {
+                self.popup_subtitle.popup.table_state.select_previous();
+                self.popup_subtitle.popup.scroll_bar_up();
+            }
+            (KeyContext::Subtitles, KeyAction::Close) => {
+                self.popup_subtitle.popup.show = false;
+                self.focus = self.table_focus();
+            }
+            (KeyContext::Subtitles, KeyAction::Select) => {
+                self.popup_subtitle.popup.show = false;
+                self.focus = self.table_focus();
+
+                if let Some(selected) = self.popup_subtitle.popup.table_state.selected()
+                    && selected < self.popup_subtitle.subtitles.len()
+                {
+                    return vec![Action::DownloadSubtitle(selected)];
+                }
+            }
+            (KeyContext::Search | KeyContext::Movies, KeyAction::Genres) => {
+                let current = Query::parse(self.input_box.text())
+                    .ok()
+                    .and_then(|query| query.genre);
+                let from = std::mem::replace(&mut self.focus, Focus::PopupGenre);
+                self.popup_genre.open(from, current.as_deref());
+            }
+            (KeyContext::Genres, KeyAction::Down) => {
+                self.popup_genre.popup.table_state.select_next();
+                self.popup_genre
+                    .popup
+                    .scroll_bar_down(self.popup_genre.len());
+            }
+            (KeyContext::Genres, KeyAction::Up) => {
+                self.popup_genre.popup.table_state.select_previous();
+                self.popup_genre.popup.scroll_bar_up();
+            }
+            (KeyContext::Genres, KeyAction::Close) => {
+                self.popup_genre.popup.show = false;
+                self.focus = self.popup_genre.from;
+            }
+            (KeyContext::Genres, KeyAction::Select) => {
+                self.popup_genre.popup.show = false;
+                self.focus = self.popup_genre.from;
+
+                if let Some(genre) = self.popup_genre.selected() {
+                    let text = with_filter(self.input_box.text(), "genre", genre);
+                    self.input_box.set_text(text);
+                    return self.search_movies();
+                }
+            }
+            (KeyContext::Notification, KeyAction::Close) => {
+                self.popup_notification.show = false;
+                self.focus = self.table_focus();
+            }
+            _ => {}
+        }
+
+        vec![]
+    }
+
+    fn search_movies(&mut self) -> Vec<Action> {
+        match Query::parse(self.input_box.text()) {
+            Ok(query) => self.movie_table.query = query,
+            Err(errors) => {
+                self.input_box.set_errors(errors);
+                return vec![];
+            }
+        }
+
+        if self.history.add(self.input_box.text()) {
+            vec![Action::SearchMovies, Action::SaveHistory]
+        } else {
+            vec![Action::SearchMovies]
+        }
+    }
+
+    /// Table shown in the movies panel, where the popups go back to
+    fn table_focus(&self) -> Focus {
+        if self.watchlist.show {
+            Focus::Watchlist
+        } else {
+            Focus::MovieTable
+        }
+    }
+
+    fn go_to_page(&self, page: u32) -> Vec<Action> {
+        let current = &self.movie_table.response.page;
+        if page >= 1 && page <= current.of && page != current.current {
+            vec![Action::GoToPage(page)]
+        } else {
+            vec![]
+        }
+    }
+
+    /// Completions of the word being typed, closed when nothing matches
+    fn refine_completion(&mut self) {
+        let (start, word) = self.input_box.word_before_cursor();
+        let items = completions(word);
+
+        if items.is_empty() {
+            self.completion.close();
+        } else {
+            self.completion.open(start, items);
+        }
+    }
+
+    fn show_history_search(&mut self, found: Option<String>) {
+        if let Some(text) = found {
+            self.input_box.set_text(text);
+        }
+        self.input_box.prompt = self.history.prompt();
+    }
+
+    /// Pasted text goes to the search box or the note being written, newlines become spaces
+    pub fn handle_paste(&mut self, text: String) -> Vec<Action> {
+        match self.context() {
+            KeyContext::Search | KeyContext::Completion => {
+                let previous = self.input_box.text().to_string();
+                self.completion.close();
+                self.input_box.insert_str(&text);
+                self.schedule_live_search(&previous);
+            }
+            KeyContext::Note => self.popup_note.input.insert_str(&text),
+            _ => {}
+        }
+        vec![]
+    }
+
+    /// Every change of the search text delays the live search again
+    fn schedule_live_search(&mut self, previous: &str) {
+        if let Some(delay) = self.live_search_delay
+            && self.input_box.text() != previous
+        {
+            self.live_search_at = Some(Instant::now() + delay);
+        }
+    }
+
+    /// Actions which wait for a pause: the live search, the details of the selected movie
+    /// and the checks of the watchlist
+    pub fn tick(&mut self, now: Instant) -> Vec<Action> {
+        let mut actions = self.live_search(now);
+
+        // Checks wait for Transmission, which adds the torrents
+        if let Some(interval) = self.watch_interval
+            && self.watch_at.is_none_or(|at| now >= at)
+            && self.transmission.is_connected()
+            && !self.watchlist.waiting().is_empty()
+        {
+            self.watch_at = Some(now + interval);
+            actions.push(Action::CheckWatchlist);
+        }
+
+        // The details are hidden while the watchlist is shown
+        if !self.watchlist.show
+            && let Some(index) = self.selected_movie()
+            && self
+                .movie_details
+                .should_fetch(&self.movie_table.response.movies[index].link, now)
+        {
+            actions.push(Action::FetchDetails(index));
+        }
+
+        actions
+    }
+
+    /// Fires the live search once the typing pauses. Incomplete filters are not searched
+    /// nor highlighted until Enter, neither is the search already shown
+    fn live_search(&mut self, now: Instant) -> Vec<Action> {
+        if self.live_search_at.is_none_or(|at| now < at) {
+            return vec![];
+        }
+        self.live_search_at = None;
+
+        if self.history.is_searching() {
+            return vec![];
+        }
+
+        match Query::parse(self.input_box.text()) {
+            Ok(query) if query != Query::default() && query != self.movie_table.query => {
+                self.movie_table.query = query;
+                vec![Action::LiveSearch]
+            }
+            _ => vec![],
+        }
+    }
+
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) -> Vec<Action> {
+        let position = Position::new(mouse.column, mouse.row);
+        let context = self.context();
+
+        let step = match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => return self.click(context, position),
+            MouseEventKind::ScrollDown => KeyAction::Down,
+            MouseEventKind::ScrollUp => KeyAction::Up,
+            _ => return vec![],
+        };
+
+        match context {
+            KeyContext::Torrents | KeyContext::Subtitles | KeyContext::Genres => {
+                self.dispatch(context, step)
+            }
+            KeyContext::Search
+            | KeyContext::Movies
+            | KeyContext::Watchlist
+            | KeyContext::Downloads => {
+                if self.areas.movie_table.contains(position) {
+                    let table = if self.watchlist.show {
+                        KeyContext::Watchlist
+                    } else {
+                        KeyContext::Movies
+                    };
+                    self.dispatch(table, step)
+                } else if self.areas.downloads.contains(position) {
+                    self.dispatch(KeyContext::Downloads, step)
+                } else {
+                    vec![]
+                }
+            }
+            _ => vec![],
+        }
+    }
+
+    fn click(&mut self, context: KeyContext, position: Position) -> Vec<Action> {
+        let double_click = self.last_click.is_some_and(|(time, last)| {
+            last == position && time.elapsed() < Duration::from_millis(400)
+        });
+        self.last_click = (!double_click).then(|| (Instant::now(), position));
+
+        match context {
+            KeyContext::Loading => vec![],
+            KeyContext::History => self.dispatch(context, KeyAction::Cancel),
+            KeyContext::Completion => self.dispatch(context, KeyAction::Close),
+            KeyContext::Page | KeyContext::Note => {
+                if !self.areas.popup.contains(position) {
+                    return self.dispatch(context, KeyAction::Close);
+                }
+                vec![]
+            }
+            KeyContext::Help | KeyContext::Notification => self.dispatch(context, KeyAction::Close),
+            KeyContext::Torrents | KeyContext::Subtitles | KeyContext::Genres => {
+                if !self.areas.popup.contains(position) {
+                    return self.dispatch(context, KeyAction::Close);
+                }
+
+                let (popup, len) = match context {
+                    KeyContext::Torrents => {
+                        let len = self.popup_torrent.torrents.len();
+                        (&mut self.popup_torrent.popup, len)
+                    }
+                    KeyContext::Subtitles => {
+                        let len = self.popup_subtitle.subtitles.len();
+                        (&mut self.popup_subtitle.popup, len)
+                    }
+                    _ => {
+                        let len = self.popup_genre.len();
+                        (&mut self.popup_genre.popup, len)
+                    }
+                };
+
+                match Areas::row(self.areas.popup, position) {
+                    Some(row) if row + popup.table_state.offset() < len => {
+                        popup.select(row + popup.table_state.offset());
+                        if double_click {
+                            return self.dispatch(context, KeyAction::Select);
+                        }
+                        vec![]
+                    }
+                    _ => vec![],
+                }
+            }
+            _ => {
+                if self.areas.input_box.contains(position) {
+                    self.focus = Focus::InputBox;
+                    if double_click {
+                        return self.dispatch(KeyContext::Search, KeyAction::Search);
+                    }
+                } else if self.watchlist.show && self.areas.movie_table.contains(position) {
+                    self.focus = Focus::Watchlist;
+                    let table_state = &mut self.watchlist.table_state;
+
+                    if let Some(row) = Areas::row(self.areas.movie_table, position)
+                        && row + table_state.offset() < self.watchlist.entries.len()
+                    {
+                        table_state.select(Some(row + table_state.offset()));
+                        if double_click {
+                            return self.dispatch(KeyContext::Watchlist, KeyAction::Torrents);
+                        }
+                    }
+                } else if self.areas.movie_table.contains(position) {
+                    self.focus = Focus::MovieTable;
+
+                    if position.y == self.areas.movie_table.bottom() - 1
+                        && let Some(page) = self.movie_table.page_at(
+                            self.areas.movie_table,
+                            &self.theme.icons,
+                            position.x,
+                        )
+                    {
+                        return self.go_to_page(page);
+                    }
+
+                    let table_state = &mut self.movie_table.table_state;
+
+                    if let Some(row) = Areas::row(self.areas.movie_table, position)
+                        && row + table_state.offset() < self.movie_table.response.movies.len()
+                    {
+                        table_state.select(Some(row + table_state.offset()));
+                        if double_click {
+                            return self.dispatch(KeyContext::Movies, KeyAction::Torrents);
+                        }
+                    }
+                } else if self.transmission.is_visible() && self.areas.downloads.contains(position)
+                {
+                    self.focus = Focus::TorrentTable;
+                    let offset = self.transmission.table_state.offset();
+
+                    if let Some(row) = Areas::row(self.areas.downloads, position)
+                        && row + offset < self.transmission.torrents.len()
+                    {
+                        self.transmission.select(row + offset);
+                        if double_click {
+                            return self.dispatch(KeyContext::Downloads, KeyAction::Toggle);
+                        }
+                    }
+                }
+                vec![]
+            }
+        }
+    }
+
+    pub fn handle_event(&mut self, event: Event) -> Vec<Action> {
+        match event {
+            // New results replace the watchlist in the movies panel
+            Event::Movies(Ok(response)) => {
+                self.movie_table.set_response(response);
+                self.watchlist.show = false;
+                self.focus = Focus::MovieTable;
+                return vec![Action::PrefetchNextPage, Action::ScanLibrary];
+            }
+            Event::Movies(Err(e)) => self.notify_error(format!("Error searching movies {e}")),
+            // The focus stays in the search box while typing
+            Event::LiveMovies(Ok(response)) => {
+                self.movie_table.set_response(response);
+                self.watchlist.show = false;
+                self.movie_table.table_state.select_first();
+                return vec![Action::ScanLibrary];
+            }
+            // Failures are not notified over the typing, Enter searches and shows them
+            Event::LiveMovies(Err(_)) => {}
+            Event::Torrents(Ok(torrents)) => {
+                self.popup_torrent.set_torrents(torrents);
+                self.popup_torrent.popup.show = true;
+                self.focus = Focus::PopupTorrent;
+            }
+            Event::Torrents(Err(e)) => self.notify_error(format!("Error searching torrents {e}")),
+            Event::Subtitles(Ok((page, subtitles))) => {
+                self.popup_subtitle.set_subtitles(page, subtitles);
+                self.popup_subtitle.popup.show = true;
+                self.focus = Focus::PopupSubtitle;
+            }
+            Event::Subtitles(Err(e)) => self.notify_error(format!("Error searching subtitles {e}")),
+            Event::SubtitleDownloaded(movie, Ok(())) => {
+                self.notify_success(format!("Subtitle {movie}.srt downloaded"));
+                return vec![Action::ScanLibrary];
+            }
+            Event::SubtitleDownloaded(movie, Err(e)) => {
+                self.notify_error(format!("Error downloading subtitle {movie}.srt {e}"));
+            }
+            Event::HistorySaved(Err(e)) => {
+                self.notify_error(format!("Error saving search history {e}"));
+            }
+            Event::HistorySaved(Ok(())) => {}
+            Event::LibraryScanned(files, subtitles) => {
+                self.library.set_files(files, subtitles);
+            }
+            Event::WatchlistSaved(Err(e)) => {
+                self.notify_error(format!("Error saving watchlist {e}"))
+            }
+            Event::WatchlistSaved(Ok(())) => {}
+            // A check skipped while another gativideo checks is only logged
+            Event::WatchlistChecked(Ok(None)) => {}
+            Event::WatchlistChecked(Ok(Some(checks))) => {
+                // The monitor saved the grabbed torrents already
+                let mut names = vec![];
+                for (movie, check) in checks {
+                    if let Check::Grabbed(torrent) = check {
+                        self.watchlist.grabbed(&movie.link, &torrent);
+                        names.push(format!("{} {torrent}", movie.name));
+                    }
+                }
+
+                // Checks which grab nothing are only logged
+                if !names.is_empty() {
+                    self.notify_success(format!("Watchlist grabbed {}", names.join(", ")));
+                    return vec![Action::ScanDownloads];
+                }
+            }
+            Event::WatchlistChecked(Err(e)) => {
+                self.notify_error(format!("Error checking watchlist {e}"))
+            }
+            Event::Details(link, details) => {
+                self.movie_details.fetched(link, details, Instant::now())
+            }
+            Event::Downloads(Ok(torrents)) => {
+                self.library.set_downloads(
+                    torrents
+                        .iter()
+                        .filter_map(|torrent| {
+                            Some((
+                                torrent.name.clone()?,
+                                torrent.percent_done.unwrap_or_default(),
+                            ))
+                        })
+                        .collect(),
+                );
+                self.transmission.connected(torrents);
+            }
+            Event::Downloads(Err(e)) => {
+                self.library.set_downloads(vec![]);
+                // Only a lost connection is notified, the status shows the retries
+                if self.transmission.is_connected() {
+                    self.notify_error(format!("Lost connection to Transmission {e}"));
+                }
+                self.transmission.disconnected(e.to_string());
+            }
+            Event::DownloadChanged(change, result) => {
+                let operation = match change {
+                    DownloadChange::Add => "adding torrent",
+                    DownloadChange::Toggle => "starting/stopping download",
+                    DownloadChange::Remove => "removing download",
+                };
+
+                match result {
+                    Ok(true) => {}
+                    Ok(false) => self.notify_error(format!("Transmission refused {operation}")),
+                    Err(e) => self.notify_error(format!("Error {operation} {e}")),
+                }
+
+                return vec![Action::ScanDownloads];
+            }
+        }
+
+        vec![]
+    }
+
+    pub fn notify(&mut self, text: String) {
+        self.popup_notification.text = text;
+        self.popup_notification.show = true;
+        self.focus = Focus::PopupNotification;
+    }
+
+    pub fn notify_error(&mut self, text: String) {
+        self.notify(format!("{}  {text}", self.theme.icons.error));
+    }
+
+    pub fn notify_success(&mut self, text: String) {
+        self.notify(format!("{}  {text}", self.theme.icons.success));
+    }
+
+    fn selected_movie(&self) -> Option<usize> {
+        self.movie_table
+            .table_state
+            .selected()
+            .filter(|&selected| selected < self.movie_table.response.movies.len())
+    }
+
+    fn selected_download(&self) -> Option<usize> {
+        self.transmission
+            .table_state
+            .selected()
+            .filter(|&selected| selected < self.transmission.torrents.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Instant};
+
+    use crossterm::event::{
+        KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    };
+    use ratatui::layout::Rect;
+    use yts_movies::{Movie, Page, Response};
+
+    use crate::app::{Action, App, Areas};
+    use crate::config::{Config, KeyContext, KeyMap, KeysToml};
+    use crate::downloads::Connection;
+    use crate::elements::Focus;
+    use crate::tasks::{DownloadChange, Event};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn typing_and_searching() {
+        let config = Config::default();
+        let mut app = App::new(&config).unwrap();
+
+        for c in "matrix".chars() {
+            assert!(app.handle_key(key(KeyCode::Char(c))).is_empty());
+        }
+        app.handle_key(key(KeyCode::Backspace));
+
+        assert_eq!(app.input_box.text(), "matri");
+        assert_eq!(app.focus, Focus::InputBox);
+        assert_eq!(
+            app.handle_key(key(KeyCode::Enter)),
+            vec![Action::SearchMovies, Action::SaveHistory]
+        );
+    }
+
+    #[test]
+    fn invalid_filters_are_not_searched() {
+        let config = Config::default();
+        let mut app = App::new(&config).unwrap();
+
+        app.handle_paste(String::from("alien rating:11"));
+        assert!(app.handle_key(key(KeyCode::Enter)).is_empty());
+
+        app.handle_key(key(KeyCode::Backspace));
+        assert_eq!(app.handle_key(key(KeyCode::Enter))[0], Action::SearchMovies);
+        assert_eq!(app.movie_table.query.rating, Some(1));
+        assert_eq!(app.movie_table.query.title, "alien");
+    }
+
+    #[test]
+    fn genre_picker_sets_the_genre_filter() {
+        let config = Config::default();
+        let mut app = App::new(&config).unwrap();
+        app.handle_paste(String::from("alien genre:drama"));
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL));
+        assert_eq!(app.context(), KeyContext::Genres);
+        assert_eq!(app.popup_genre.selected(), Some(Some("drama")));
+
+        app.handle_key(key(KeyCode::Up));
+        assert_eq!(app.popup_genre.selected(), Some(Some("documentary")));
+        assert!(app.handle_key(key(KeyCode::Esc)).is_empty());
+        assert_eq!(app.focus, Focus::InputBox);
+
+        app.focus = Focus::MovieTable;
+        app.handle_key(key(KeyCode::Char('f')));
+        app.handle_key(key(KeyCode::Char('k')));
+        assert_eq!(app.handle_key(key(KeyCode::Enter))[0], Action::SearchMovies);
+        assert_eq!(app.input_box.text(), "alien genre:documentary");
+        assert_eq!(app.movie_table.query.genre.as_deref(), Some("documentary"));
+        assert_eq!(app.focus, Focus::MovieTable);
+    }
+
+    #[test]
+    fn tab_completes_filters() {
+        let config = Config::default();
+        let mut app = App::new(&config).unwrap();
+        app.handle_paste(String::from("alien or"));
+
+        app.handle_key(key(KeyCode::Tab));
+        assert_eq!(app.input_box.text(), "alien order:");
+        assert!(!app.completion.is_open());
+
+        app.handle_key(key(KeyCode::Char('l')));
+        app.handle_key(key(KeyCode::Tab));
+        assert_eq!(app.context(), KeyContext::Completion);
+        assert_eq!(app.completion.items, vec!["order:latest", "order:likes"]);
+
+        app.handle_key(key(KeyCode::Char('i')));
+        assert_eq!(app.completion.items, vec!["order:likes"]);
+        assert!(app.handle_key(key(KeyCode::Enter)).is_empty());
+        assert_eq!(app.input_box.text(), "alien order:likes ");
+        assert_eq!(app.context(), KeyContext::Search);
+
+        // Nothing to complete moves the focus
+        app.handle_key(key(KeyCode::Tab));
+        assert_eq!(app.focus, Focus::MovieTable);
+    }
+
+    #[test]
+    fn live_search_waits_for_a_pause() {
+        let config = Config {
+            live_search: true,
+            ..Config::default()
+        };
+        let mut app = App::new(&config).unwrap();
+        let later = || Instant::now() + config.live_search_delay;
+
+        app.handle_key(key(KeyCode::Char('a')));
+        assert!(app.tick(Instant::now()).is_empty());
+        app.handle_key(key(KeyCode::Char('l')));
+        assert_eq!(app.tick(later()), vec![Action::LiveSearch]);
+        assert_eq!(app.movie_table.query.title, "al");
+        assert!(app.tick(later()).is_empty());
+
+        // Unfinished filters and the same search again are skipped
+        app.handle_paste(String::from(" year:"));
+        assert!(app.tick(later()).is_empty());
+        app.handle_paste(String::from("1999"));
+        assert_eq!(app.tick(later()), vec![Action::LiveSearch]);
+        app.handle_key(key(KeyCode::Char(' ')));
+        assert!(app.tick(later()).is_empty());
+        assert_eq!(app.focus, Focus::InputBox);
+
+        let config = Config::default();
+        let mut app = App::new(&config).unwrap();
+        app.handle_key(key(KeyCode::Char('a')));
+        assert!(app.tick(later()).is_empty());
+    }
+
+    #[test]
+    fn tab_cycles_focus_without_downloads() {
+        let config = Config::default();
+        let mut app = App::new(&config).unwrap();
+
+        app.handle_key(key(KeyCode::Tab));
+        assert_eq!(app.focus, Focus::MovieTable);
+
+        app.handle_key(key(KeyCode::Tab));
+        assert_eq!(app.focus, Focus::InputBox);
+    }
+
+    #[test]
+    fn movie_table_keys_need_movies() {
+        let config = Config::default();
+        let mut app = App::new(&config).unwrap();
+        app.focus = Focus::MovieTable;
+
+        assert!(app.handle_key(key(KeyCode::Char('t'))).is_empty());
+        assert!(app.handle_key(key(KeyCode::Char('s'))).is_empty());
+        assert_eq!(
+            app.handle_key(key(KeyCode::Char('l'))),
+            vec![Action::NextPage]
+        );
+        assert_eq!(app.handle_key(key(KeyCode::Char('q'))), vec![Action::Quit]);
+    }
+
+    #[test]
+    fn configured_keys_are_dispatched() {
+        let config = Config {
+            keys: KeyMap::new(HashMap::from([(
+                String::from("movies"),
+                HashMap::from([(String::from("next_page"), KeysToml::One(String::from("n")))]),
+            )]))
+            .unwrap(),
+            ..Config::default()
+        };
+        let mut app = App::new(&config).unwrap();
+        app.focus = Focus::MovieTable;
+
+        assert!(app.handle_key(key(KeyCode::Char('l'))).is_empty());
+        assert_eq!(
+            app.handle_key(key(KeyCode::Char('n'))),
+            vec![Action::NextPage]
+        );
+        assert_eq!(app.handle_key(key(KeyCode::Right)), vec![]);
+    }
+
+    #[test]
+    fn escape_cancels_pending_request() {
+        let config = Config::default();
+        let mut app = App::new(&config).unwrap();
+        app.popup_loading.show = true;
+
+        assert!(app.handle_key(key(KeyCode::Char('a'))).is_empty());
+        assert_eq!(
+            app.handle_key(key(KeyCode::Esc)),
+            vec![Action::CancelRequest]
+        );
+        assert!(app.input_box.text().is_empty());
+    }
+
+    #[test]
+    fn help_shows_keys_of_the_focused_panel() {
+        let config = Config::default();
+        let mut app = App::new(&config).unwrap();
+
+        app.handle_key(key(KeyCode::Char('?')));
+        assert!(!app.popup_help.show);
+        assert_eq!(app.input_box.text(), "?");
+
+        app.focus = Focus::MovieTable;
+        app.handle_key(key(KeyCode::Char('?')));
+        assert!(app.popup_help.show);
+        assert_eq!(app.popup_help.context, KeyContext::Movies);
+        assert_eq!(app.context(), KeyContext::Help);
+
+        // Esc closes the help instead of quitting
+        assert!(app.handle_key(key(KeyCode::Esc)).is_empty());
+        assert!(!app.popup_help.show);
+        assert_eq!(app.focus, Focus::MovieTable);
+
+        app.focus = Focus::InputBox;
+        app.handle_key(key(KeyCode::F(1)));
+        assert_eq!(app.popup_help.context, KeyContext::Search);
+    }
+
+    #[test]
+    fn go_to_a_page_by_number_key_or_footer() {
+        let config = Config::default();
+        let mut app = App::new(&config).unwrap();
+
+        let movie = |name: &str| Movie {
+            name: name.to_string(),
+            year: 1999,
+            rating: 8.0,
+            genres: vec![],
+            link: name.to_string(),
+        };
+        let page = |current, movies| {
+            Event::Movies(Ok(Response {
+                page: Page {
+                    current,
+                    of: 12,
+                    total: 240,
+                },
+                movies,
+            }))
+        };
+
+        // A shorter page moves the selection to its last movie
+        app.handle_event(page(3, vec![movie("a"), movie("b"), movie("c")]));
+        app.movie_table.table_state.select(Some(2));
+        app.handle_event(page(4, vec![movie("d")]));
+        assert_eq!(app.movie_table.table_state.selected(), Some(0));
+
+        assert_eq!(
+            app.handle_key(key(KeyCode::Char('H'))),
+            vec![Action::GoToPage(1)]
+        );
+        assert_eq!(
+            app.handle_key(key(KeyCode::Char('L'))),
+            vec![Action::GoToPage(12)]
+        );
+
+        app.handle_key(key(KeyCode::Char(':')));
+        assert_eq!(app.context(), KeyContext::Page);
+        for c in ['x', '1', '3', '0'] {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+        assert_eq!(app.popup_page.text, "13");
+        assert!(app.handle_key(key(KeyCode::Enter)).is_empty());
+
+        app.handle_key(key(KeyCode::Backspace));
+        assert_eq!(
+            app.handle_key(key(KeyCode::Enter)),
+            vec![Action::GoToPage(1)]
+        );
+        assert_eq!(app.focus, Focus::MovieTable);
+
+        // First, previous, next and last controls of the footer
+        let area = Rect::new(0, 3, 80, 10);
+        app.areas.movie_table = area;
+        let pages = (0..80)
+            .filter_map(|x| app.movie_table.page_at(area, &config.theme.icons, x))
+            .collect::<Vec<u32>>();
+        assert_eq!(pages, vec![1, 3, 5, 12]);
+
+        let next = (0..80)
+            .find(|&x| app.movie_table.page_at(area, &config.theme.icons, x) == Some(5))
+            .unwrap();
+        let click = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: next,
+            row: area.bottom() - 1,
+            modifiers: KeyModifiers::NONE,
+        };
+        assert_eq!(app.handle_mouse(click), vec![Action::GoToPage(5)]);
+    }
+
+    #[test]
+    fn watchlist_keeps_movies_with_notes() {
+        let config = Config::default();
+        let mut app = App::new(&config).unwrap();
+
+        app.handle_event(Event::Movies(Ok(Response {
+            page: Page {
+                current: 1,
+                of: 1,
+                total: 1,
+            },
+            movies: vec![Movie {
+                name: String::from("Alien"),
+                year: 1979,
+                rating: 8.5,
+                genres: vec![],
+                link: String::from("alien"),
+            }],
+        })));
+
+        assert_eq!(
+            app.handle_key(key(KeyCode::Char('w'))),
+            vec![Action::SaveWatchlist]
+        );
+        app.handle_key(key(KeyCode::Esc));
+        assert!(app.handle_key(key(KeyCode::Char('w'))).is_empty());
+        app.handle_key(key(KeyCode::Esc));
+        assert_eq!(app.watchlist.entries.len(), 1);
+
+        app.handle_key(key(KeyCode::Char('W')));
+        assert_eq!(app.context(), KeyContext::Watchlist);
+        assert_eq!(
+            app.handle_key(key(KeyCode::Char('t'))),
+            vec![Action::WatchlistTorrents(0)]
+        );
+
+        // Popups close back to the watchlist
+        app.handle_event(Event::Torrents(Ok(vec![])));
+        app.handle_key(key(KeyCode::Esc));
+        assert_eq!(app.focus, Focus::Watchlist);
+
+        app.handle_key(key(KeyCode::Char('n')));
+        assert_eq!(app.context(), KeyContext::Note);
+        app.handle_paste(String::from("director's cut"));
+        app.handle_key(key(KeyCode::Char('q')));
+        assert_eq!(
+            app.handle_key(key(KeyCode::Enter)),
+            vec![Action::SaveWatchlist]
+        );
+        assert_eq!(app.watchlist.entries[0].note, "director's cutq");
+        assert_eq!(app.focus, Focus::Watchlist);
+
+        assert_eq!(
+            app.handle_key(key(KeyCode::Char('d'))),
+            vec![Action::SaveWatchlist]
+        );
+        assert!(app.watchlist.entries.is_empty());
+
+        app.handle_key(key(KeyCode::Esc));
+        assert!(!app.watchlist.show);
+        assert_eq!(app.focus, Focus::MovieTable);
+    }
+
+    #[test]
+    fn mouse_clicks_use_the_rendered_areas() {
+        let config = Config::default();
+        let mut app = App::new(&config).unwrap();
+        app.areas = Areas {
+            input_box: Rect::new(0, 0, 80, 3),
+            movie_table: Rect::new(0, 3, 80, 10),
+            ..Areas::default()
+        };
+
+        let click = |column, row| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+
+        assert!(app.handle_mouse(click(10, 6)).is_empty());
+        assert_eq!(app.focus, Focus::MovieTable);
+
+        app.handle_mouse(click(10, 1));
+        assert_eq!(app.focus, Focus::InputBox);
+        assert_eq!(app.handle_mouse(click(10, 1)), vec![Action::SearchMovies]);
+
+        app.notify(String::from("done"));
+        app.handle_mouse(click(70, 20));
+        assert!(!app.popup_notification.show);
+        assert_eq!(app.focus, Focus::MovieTable);
+    }
+
+    #[test]
+    fn transmission_failures_are_notified() {
+        let config = Config::default();
+        let mut app = App::new(&config).unwrap();
+
+        let actions = app.handle_event(Event::DownloadChanged(
+            DownloadChange::Remove,
+            Err("connection refused".into()),
+        ));
+
+        assert_eq!(actions, vec![Action::ScanDownloads]);
+        assert_eq!(app.focus, Focus::PopupNotification);
+        assert!(app.popup_notification.text.contains("removing download"));
+    }
+
+    #[test]
+    fn starts_disconnected_without_notifying() {
+        let config = Config::default();
+        let mut app = App::new(&config).unwrap();
+
+        app.handle_event(Event::Downloads(Err("connection refused".into())));
+
+        assert!(!app.popup_notification.show);
+        assert!(!app.transmission.should_scan());
+        assert!(app.transmission.is_visible());
+        assert!(matches!(
+            app.transmission.connection,
+            Connection::Disconnected { attempts: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn lost_connection_is_notified_once() {
+        let config = Config::default();
+        let mut app = App::new(&config).unwrap();
+
+        app.handle_event(Event::Downloads(Ok(vec![])));
+        assert!(app.transmission.is_connected());
+
+        app.handle_event(Event::Downloads(Err("timeout".into())));
+        app.handle_key(key(KeyCode::Esc));
+        app.handle_event(Event::Downloads(Err("timeout".into())));
+
+        assert!(!app.popup_notification.show);
+        assert!(matches!(
+            app.transmission.connection,
+            Connection::Disconnected { attempts: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn notification_closes_back_to_movie_table() {
+        let config = Config::default();
+        let mut app = App::new(&config).unwrap();
+
+        app.notify(String::from("error"));
+        assert_eq!(app.focus, Focus::PopupNotification);
+
+        app.handle_key(key(KeyCode::Esc));
+        assert_eq!(app.focus, Focus::MovieTable);
+        assert!(!app.popup_notification.show);
+    }
+}
@@ -0,0 +1,73 @@
+use clap::{Parser, Subcommand};
+
+/// TUI app to download YTS movies and opensubtitles subtitles.
+/// Run without a subcommand to open the TUI.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Print the output as JSON
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Search YTS movies. Accepts the same filters as the search box (ex: "matrix year:1999")
+    Search {
+        query: String,
+        #[arg(short, long, default_value_t = 1)]
+        page: u32,
+    },
+    /// List the torrents of a movie
+    Torrents {
+        /// Movie search (same filters as the search box)
+        movie: String,
+        /// Number of the movie in the page of the search results
+        #[arg(short, long, default_value_t = 1)]
+        number: usize,
+        /// Page of the search results
+        #[arg(short, long, default_value_t = 1)]
+        page: u32,
+    },
+    /// Add a torrent url to Transmission
+    Add { url: String },
+    /// List and download subtitles of a movie
+    Subs {
+        /// Movie search (same filters as the search box)
+        movie: String,
+        /// Number of the movie in the page of the search results
+        #[arg(short, long, default_value_t = 1)]
+        number: usize,
+        /// Page of the search results
+        #[arg(short, long, default_value_t = 1)]
+        page: u32,
+        /// Subtitle languages (overrides the config)
+        #[arg(short, long)]
+        lang: Vec<String>,
+        /// Download the subtitle with this number instead of listing them
+        #[arg(short, long)]
+        download: Option<usize>,
+    },
+    /// List Transmission downloads
+    Downloads,
+    /// Start or stop a Transmission download
+    Toggle {
+        /// Number of the download in the downloads list
+        number: usize,
+    },
+    /// Remove a Transmission download (downloaded files are kept)
+    Remove {
+        /// Number of the download in the downloads list
+        number: usize,
+    },
+    /// Check the watchlist every interval of the [watch] config, adding the best torrent
+    /// of each movie to Transmission once one passes the rules
+    Watch {
+        /// Check once and exit
+        #[arg(long)]
+        once: bool,
+    },
+}
@@ -0,0 +1,572 @@
+use std::path::Path;
+
+use anyhow::{Context, bail};
+use opensubs::Language;
+use serde::Serialize;
+use yts_movies::Movie;
+
+use crate::cli::Command;
+use crate::config::{Config, configuration};
+use crate::downloads::Transmission;
+use crate::elements::{MovieTable, PopupSubtitle, PopupTorrent, Query};
+use crate::tasks::{Check, Monitor};
+
+#[derive(Serialize)]
+struct MovieOutput {
+    number: usize,
+    year: u64,
+    name: String,
+    genres: Vec<String>,
+    rating: f64,
+}
+
+#[derive(Serialize)]
+struct SearchOutput {
+    page: u32,
+    pages: u32,
+    total: u32,
+    movies: Vec<MovieOutput>,
+}
+
+#[derive(Serialize)]
+struct TorrentOutput {
+    number: usize,
+    quality: String,
+    size: String,
+    language: String,
+    runtime: String,
+    peers_seeds: String,
+    link: String,
+}
+
+#[derive(Serialize)]
+struct SubtitleOutput {
+    number: usize,
+    movie: String,
+    language: String,
+    cd: String,
+    uploaded: String,
+    downloads: u64,
+    rating: f64,
+    download_link: String,
+}
+
+#[derive(Serialize)]
+struct DownloadOutput {
+    number: usize,
+    name: String,
+    size_gb: f64,
+    percent_done: f64,
+    status: &'static str,
+    peers_sending: i64,
+    peers_connected: i64,
+}
+
+#[derive(Serialize)]
+struct CheckOutput {
+    name: String,
+    year: u64,
+    grabbed: bool,
+    result: String,
+}
+
+#[derive(Serialize)]
+struct ResultOutput {
+    ok: bool,
+    message: String,
+}
+
+/// Under --json a failure is printed as a result too, besides the error of main
+pub async fn run(command: Command, json: bool) -> anyhow::Result<()> {
+    let result = execute(command, json).await;
+
+    if json && let Err(e) = &result {
+        print_result(json, false, e.to_string())?;
+    }
+
+    result
+}
+
+async fn execute(command: Command, json: bool) -> anyhow::Result<()> {
+    let config = configuration()?;
+
+    match command {
+        Command::Search { query, page } => search(&config, &query, page, json).await,
+        Command::Torrents {
+            movie,
+            number,
+            page,
+        } => torrents(&config, &movie, number, page, json).await,
+        Command::Add { url } => add(&config, &url, json).await,
+        Command::Subs {
+            movie,
+            number,
+            page,
+            lang,
+            download,
+        } => subtitles(&config, &movie, number, page, &lang, download, json).await,
+        Command::Downloads => downloads(&config, json).await,
+        Command::Toggle { number } => toggle(&config, number, json).await,
+        Command::Remove { number } => remove(&config, number, json).await,
+        Command::Watch { once } => watch(&config, once, json).await,
+    }
+}
+
+async fn search(config: &Config, query: &str, page: u32, json: bool) -> anyhow::Result<()> {
+    let mut movie_table = MovieTable::new(
+        &config.yts_host,
+        config.yts_order.clone(),
+        config.page_cache(),
+    );
+    movie_table.query = parse_query(query)?;
+    movie_table.response = movie_table
+        .search_page(page)
+        .await
+        .map_err(anyhow::Error::msg)?;
+
+    let movies: Vec<MovieOutput> = movie_table
+        .response
+        .movies
+        .iter()
+        .enumerate()
+        .map(|(i, movie)| MovieOutput {
+            number: i + 1,
+            year: movie.year.into(),
+            name: movie.name.clone(),
+            genres: movie.genres.iter().map(|g| g.to_string()).collect(),
+            rating: movie.rating.into(),
+        })
+        .collect();
+
+    if json {
+        let page = &movie_table.response.page;
+        return print_json(&SearchOutput {
+            page: page.current,
+            pages: page.of,
+            total: page.total,
+            movies,
+        });
+    }
+
+    print_table(
+        &["#", "Year", "Name", "Genre", "Rating"],
+        movies
+            .into_iter()
+            .map(|m| {
+                vec![
+                    m.number.to_string(),
+                    m.year.to_string(),
+                    m.name,
+                    m.genres.join("/"),
+                    m.rating.to_string(),
+                ]
+            })
+            .collect(),
+    );
+    println!("{}", movie_table.footer().trim());
+
+    Ok(())
+}
+
+async fn torrents(
+    config: &Config,
+    query: &str,
+    number: usize,
+    page: u32,
+    json: bool,
+) -> anyhow::Result<()> {
+    let movie = find_movie(config, query, number, page).await?;
+
+    let torrents = PopupTorrent::new(&config.yts_host)
+        .search_torrents(&movie)
+        .await
+        .map_err(anyhow::Error::msg)?;
+
+    let torrents: Vec<TorrentOutput> = torrents
+        .iter()
+        .enumerate()
+        .map(|(i, torrent)| {
+            let quality: &str = (&torrent.quality).into();
+            TorrentOutput {
+                number: i + 1,
+                quality: quality.to_owned(),
+                size: torrent.size.clone(),
+                language: torrent.language.clone(),
+                runtime: torrent.runtime.clone(),
+                peers_seeds: torrent.peers_seeds.clone(),
+                link: torrent.link.clone(),
+            }
+        })
+        .collect();
+
+    if json {
+        return print_json(&torrents);
+    }
+
+    println!("{} ({})", movie.name, movie.year);
+    print_table(
+        &[
+            "#",
+            "Quality",
+            "Size",
+            "Language",
+            "Runtime",
+            "Peers/Seeds",
+            "Link",
+        ],
+        torrents
+            .into_iter()
+            .map(|t| {
+                vec![
+                    t.number.to_string(),
+                    t.quality,
+                    t.size,
+                    t.language,
+                    t.runtime,
+                    t.peers_seeds,
+                    t.link,
+                ]
+            })
+            .collect(),
+    );
+
+    Ok(())
+}
+
+async fn subtitles(
+    config: &Config,
+    query: &str,
+    number: usize,
+    page: u32,
+    languages: &[String],
+    download: Option<usize>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let languages = if languages.is_empty() {
+        config.opensubs_langs.clone()
+    } else {
+        languages
+            .iter()
+            .map(|lang| {
+                Language::try_from(lang.as_str())
+                    .map_err(|_| anyhow::anyhow!("Failed to convert '{lang}' to Opensubs Language"))
+            })
+            .collect::<anyhow::Result<Vec<Language>>>()?
+    };
+
+    let movie = find_movie(config, query, number, page).await?;
+
+    let popup_subtitle = PopupSubtitle::new(
+        &languages,
+        config.opensubs_order.clone(),
+        &config.yts_download_dir,
+    );
+
+    let (_, subtitles) = popup_subtitle
+        .search_subtitles(&movie)
+        .await
+        .map_err(anyhow::Error::msg)?;
+
+    if let Some(download) = download {
+        let sub = download
+            .checked_sub(1)
+            .and_then(|index| subtitles.get(index))
+            .with_context(|| format!("No subtitle number {download} found"))?;
+
+        popup_subtitle
+            .download_subtitle(&sub.download_link, &sub.movie)
+            .await?;
+
+        let output = Path::new(&config.yts_download_dir).join(format!("{}.srt", sub.movie));
+
+        return print_result(
+            json,
+            true,
+            format!("Subtitle downloaded to {}", output.display()),
+        );
+    }
+
+    let subtitles: Vec<SubtitleOutput> = subtitles
+        .iter()
+        .enumerate()
+        .map(|(i, sub)| SubtitleOutput {
+            number: i + 1,
+            movie: sub.movie.clone(),
+            language: sub.language.clone(),
+            cd: sub.cd.clone(),
+            uploaded: sub.uploaded.clone(),
+            downloads: sub.downloads.into(),
+            rating: sub.rating.into(),
+            download_link: sub.download_link.clone(),
+        })
+        .collect();
+
+    if json {
+        return print_json(&subtitles);
+    }
+
+    println!("{} ({})", movie.name, movie.year);
+    print_table(
+        &[
+            "#",
+            "Movie",
+            "Language",
+            "CD",
+            "Uploaded",
+            "Downloads",
+            "Rating",
+        ],
+        subtitles
+            .into_iter()
+            .map(|s| {
+                vec![
+                    s.number.to_string(),
+                    s.movie,
+                    s.language,
+                    s.cd,
+                    s.uploaded,
+                    s.downloads.to_string(),
+                    s.rating.to_string(),
+                ]
+            })
+            .collect(),
+    );
+
+    Ok(())
+}
+
+async fn add(config: &Config, url: &str, json: bool) -> anyhow::Result<()> {
+    let transmission = transmission(config)?;
+
+    let added = transmission.add(url).await.map_err(anyhow::Error::msg)?;
+
+    if added {
+        print_result(json, true, String::from("Torrent added"))
+    } else {
+        bail!("Transmission refused the torrent")
+    }
+}
+
+async fn downloads(config: &Config, json: bool) -> anyhow::Result<()> {
+    let transmission = connected_transmission(config).await?;
+
+    let downloads: Vec<DownloadOutput> = transmission
+        .torrents
+        .iter()
+        .enumerate()
+        .map(|(i, torrent)| DownloadOutput {
+            number: i + 1,
+            name: torrent.name.clone().unwrap_or_default(),
+            size_gb: torrent
+                .size_when_done
+                .map_or(0.0, |p| p as f64 / 1024.0 / 1024.0 / 1024.0),
+            percent_done: torrent.percent_done.map_or(0.0, |p| p as f64 * 100.0),
+            status: Transmission::torrent_status(torrent),
+            peers_sending: torrent.peers_sending_to_us.unwrap_or_default(),
+            peers_connected: torrent.peers_connected.unwrap_or_default(),
+        })
+        .collect();
+
+    if json {
+        return print_json(&downloads);
+    }
+
+    print_table(
+        &["#", "Name", "Size", "Downloaded", "Status", "Peers/Seeds"],
+        downloads
+            .into_iter()
+            .map(|d| {
+                vec![
+                    d.number.to_string(),
+                    d.name,
+                    format!("{:.2}GB", d.size_gb),
+                    format!("{:.2}%", d.percent_done),
+                    d.status.to_string(),
+                    format!("{}/{}", d.peers_sending, d.peers_connected),
+                ]
+            })
+            .collect(),
+    );
+
+    Ok(())
+}
+
+async fn toggle(config: &Config, number: usize, json: bool) -> anyhow::Result<()> {
+    let transmission = connected_transmission(config).await?;
+    let index = download_index(&transmission, number)?;
+
+    if transmission
+        .toggle(index)
+        .await
+        .map_err(anyhow::Error::msg)?
+    {
+        print_result(json, true, format!("Download {number} started/stopped"))
+    } else {
+        bail!("Transmission refused to start/stop download {number}")
+    }
+}
+
+async fn remove(config: &Config, number: usize, json: bool) -> anyhow::Result<()> {
+    let transmission = connected_transmission(config).await?;
+    let index = download_index(&transmission, number)?;
+
+    if transmission
+        .remove(index)
+        .await
+        .map_err(anyhow::Error::msg)?
+    {
+        print_result(json, true, format!("Download {number} removed"))
+    } else {
+        bail!("Transmission refused to remove download {number}")
+    }
+}
+
+async fn watch(config: &Config, once: bool, json: bool) -> anyhow::Result<()> {
+    let monitor = Monitor::new(config)?;
+
+    loop {
+        // The file is read on each check, so movies added meanwhile from the TUI are checked too
+        match monitor.check().await {
+            Ok(Some(checks)) => print_checks(checks, json)?,
+            Ok(None) => print_result(
+                json,
+                true,
+                String::from("Skipped, another gativideo is checking the watchlist"),
+            )?,
+            // A failed check, like one while the TUI checks, is retried on the next interval
+            Err(e) if !once && json => {
+                print_result(json, false, format!("Error checking watchlist {e}"))?
+            }
+            Err(e) if !once => eprintln!("Error checking watchlist {e}"),
+            Err(e) => return Err(e),
+        }
+
+        if once {
+            return Ok(());
+        }
+        tokio::time::sleep(config.watch_interval).await;
+    }
+}
+
+fn print_checks(checks: Vec<(Movie, Check)>, json: bool) -> anyhow::Result<()> {
+    let checks: Vec<CheckOutput> = checks
+        .into_iter()
+        .map(|(movie, check)| CheckOutput {
+            name: movie.name,
+            year: movie.year.into(),
+            grabbed: matches!(check, Check::Grabbed(_)),
+            result: check.to_string(),
+        })
+        .collect();
+
+    if json {
+        print_json(&checks)?;
+    } else if checks.is_empty() {
+        println!("No movies waiting in the watchlist");
+    } else {
+        for check in checks {
+            println!("{} ({}): {}", check.name, check.year, check.result);
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_query(query: &str) -> anyhow::Result<Query> {
+    Query::parse(query).map_err(|errors| {
+        let messages = errors
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<String>>();
+        anyhow::anyhow!("Invalid search '{query}': {}", messages.join("; "))
+    })
+}
+
+async fn find_movie(
+    config: &Config,
+    query: &str,
+    number: usize,
+    page: u32,
+) -> anyhow::Result<Movie> {
+    let mut movie_table = MovieTable::new(
+        &config.yts_host,
+        config.yts_order.clone(),
+        config.page_cache(),
+    );
+    movie_table.query = parse_query(query)?;
+
+    let response = movie_table
+        .search_page(page)
+        .await
+        .map_err(anyhow::Error::msg)?;
+
+    number
+        .checked_sub(1)
+        .and_then(|index| response.movies.into_iter().nth(index))
+        .with_context(|| {
+            format!("No movie number {number} found in page {page} searching '{query}'")
+        })
+}
+
+fn transmission(config: &Config) -> anyhow::Result<Transmission> {
+    Transmission::new(
+        config.transmission_host.clone(),
+        config.transmission_username.clone(),
+        config.transmission_password.clone(),
+        config.yts_download_dir.clone(),
+    )
+}
+
+async fn connected_transmission(config: &Config) -> anyhow::Result<Transmission> {
+    let mut transmission = transmission(config)?;
+    let torrents = transmission.scan().await.map_err(anyhow::Error::msg)?;
+    transmission.connected(torrents);
+    Ok(transmission)
+}
+
+fn download_index(transmission: &Transmission, number: usize) -> anyhow::Result<usize> {
+    match number.checked_sub(1) {
+        Some(index) if index < transmission.torrents.len() => Ok(index),
+        _ => bail!("No download number {number} found"),
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn print_result(json: bool, ok: bool, message: String) -> anyhow::Result<()> {
+    if json {
+        print_json(&ResultOutput { ok, message })
+    } else {
+        println!("{message}");
+        Ok(())
+    }
+}
+
+fn print_table(header: &[&str], rows: Vec<Vec<String>>) {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{cell:<width$}"))
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", line(header.to_vec()));
+    for row in &rows {
+        println!("{}", line(row.iter().map(String::as_str).collect()));
+    }
+}
@@ -0,0 +1,5 @@
+mod args;
+mod commands;
+
+pub use args::*;
+pub use commands::*;
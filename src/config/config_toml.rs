@@ -1,13 +1,49 @@
-use std::{env, fs};
+use std::{collections::HashMap, env, fs, path::PathBuf, time::Duration};
 
 use opensubs::Language;
 use serde::Deserialize;
 
+use crate::{
+    config::{KeyMap, KeysToml, Theme, ThemeToml},
+    elements::{Graphics, PageCache, parse_size},
+    tasks::WatchRules,
+};
+
 #[derive(Deserialize, Debug)]
 struct ConfigToml {
     pub yts: Option<Yts>,
     pub opensubs: Option<Opensubs>,
     pub transmission: Option<Transmission>,
+    pub keys: Option<HashMap<String, HashMap<String, KeysToml>>>,
+    pub theme: Option<ThemeToml>,
+    pub history: Option<History>,
+    pub ui: Option<Ui>,
+    pub watch: Option<Watch>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Watch {
+    /// Whether the TUI checks the watchlist in the background
+    pub auto: Option<bool>,
+    /// Minutes between checks
+    pub interval: Option<u64>,
+    pub min_quality: Option<String>,
+    pub codec: Option<String>,
+    pub min_seeds: Option<u32>,
+    pub max_size: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Ui {
+    pub live_search: Option<bool>,
+    /// Milliseconds without typing before a live search
+    pub live_search_delay: Option<u64>,
+    pub posters: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct History {
+    pub size: Option<usize>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -22,6 +58,8 @@ struct Yts {
     pub host: Option<String>,
     pub download_dir: Option<String>,
     pub order: Option<String>,
+    /// Minutes a page of results is kept in the cache
+    pub cache_ttl: Option<u64>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -35,11 +73,26 @@ pub struct Config {
     pub yts_host: String,
     pub yts_download_dir: String,
     pub yts_order: yts_movies::OrderBy,
+    pub yts_cache_ttl: Duration,
+    pub yts_cache_dir: PathBuf,
     pub opensubs_langs: Vec<Language>,
     pub opensubs_order: opensubs::OrderBy,
     pub transmission_host: String,
     pub transmission_username: Option<String>,
     pub transmission_password: Option<String>,
+    pub keys: KeyMap,
+    pub theme: Theme,
+    pub history_path: PathBuf,
+    pub history_size: usize,
+    pub watchlist_path: PathBuf,
+    pub watch_auto: bool,
+    pub watch_interval: Duration,
+    pub watch_rules: WatchRules,
+    pub watch_log_path: PathBuf,
+    pub live_search: bool,
+    pub live_search_delay: Duration,
+    pub posters: Graphics,
+    pub poster_dir: PathBuf,
 }
 
 impl From<ConfigToml> for Config {
@@ -57,6 +110,9 @@ impl From<ConfigToml> for Config {
                 config.yts_order = yts_movies::OrderBy::try_from(order.as_str())
                     .unwrap_or_else(|_| panic!("Failed to convert '{order}' to YTS Order"));
             }
+            if let Some(ttl) = yts.cache_ttl {
+                config.yts_cache_ttl = Duration::from_secs(ttl * 60);
+            }
         }
 
         if let Some(opensubs) = value.opensubs {
@@ -88,6 +144,71 @@ impl From<ConfigToml> for Config {
             config.transmission_password = transmission.password;
         }
 
+        if let Some(keys) = value.keys {
+            config.keys =
+                KeyMap::new(keys).unwrap_or_else(|e| panic!("Failed to load [keys] config: {e}"));
+        }
+
+        if let Some(history) = value.history
+            && let Some(size) = history.size
+        {
+            config.history_size = size;
+        }
+
+        if let Some(ui) = value.ui {
+            if let Some(live_search) = ui.live_search {
+                config.live_search = live_search;
+            }
+            if let Some(delay) = ui.live_search_delay {
+                config.live_search_delay = Duration::from_millis(delay);
+            }
+            if let Some(posters) = ui.posters {
+                config.posters = Graphics::try_from(posters.as_str())
+                    .unwrap_or_else(|e| panic!("Failed to convert posters: {e}"));
+            }
+        }
+
+        if let Some(watch) = value.watch {
+            if let Some(auto) = watch.auto {
+                config.watch_auto = auto;
+            }
+            if let Some(interval) = watch.interval {
+                config.watch_interval = Duration::from_secs(interval.max(1) * 60);
+            }
+            if let Some(quality) = watch.min_quality {
+                let resolution =
+                    quality
+                        .trim_end_matches(['p', 'P'])
+                        .parse()
+                        .unwrap_or_else(|_| {
+                            panic!("Failed to convert '{quality}' to a minimum quality")
+                        });
+                config.watch_rules.min_quality = Some(resolution);
+            }
+            if let Some(codec) = watch.codec {
+                let codec = codec.to_lowercase();
+                if codec != "x264" && codec != "x265" {
+                    panic!("Failed to convert '{codec}' to a codec");
+                }
+                config.watch_rules.codec = Some(codec);
+            }
+            if let Some(seeds) = watch.min_seeds {
+                config.watch_rules.min_seeds = seeds;
+            }
+            if let Some(size) = watch.max_size {
+                let bytes = parse_size(&size);
+                if bytes <= 0.0 {
+                    panic!("Failed to convert '{size}' to a maximum size");
+                }
+                config.watch_rules.max_size = Some(bytes);
+            }
+        }
+
+        if let Some(theme) = value.theme {
+            config.theme =
+                Theme::new(theme).unwrap_or_else(|e| panic!("Failed to load [theme] config: {e}"));
+        }
+
         config
     }
 }
@@ -104,15 +225,45 @@ impl Default for Config {
                     .expect("Error converting HOME var to string")
             ),
             yts_order: yts_movies::OrderBy::Rating,
+            yts_cache_ttl: Duration::from_secs(60 * 60),
+            yts_cache_dir: xdg_dir("XDG_CACHE_HOME", ".cache").join("gativideo/pages"),
             opensubs_langs: vec![Language::Spanish],
             opensubs_order: opensubs::OrderBy::Downloads,
             transmission_host: "http://127.0.0.1:9091/transmission/rpc".to_string(),
             transmission_username: None,
             transmission_password: None,
+            keys: KeyMap::default(),
+            theme: Theme::default(),
+            history_path: xdg_dir("XDG_STATE_HOME", ".local/state").join("gativideo/history"),
+            history_size: 1000,
+            watchlist_path: xdg_dir("XDG_DATA_HOME", ".local/share")
+                .join("gativideo/watchlist.json"),
+            watch_auto: false,
+            watch_interval: Duration::from_secs(60 * 60),
+            watch_rules: WatchRules::default(),
+            watch_log_path: xdg_dir("XDG_STATE_HOME", ".local/state").join("gativideo/watch.log"),
+            live_search: false,
+            live_search_delay: Duration::from_millis(500),
+            posters: Graphics::detect(),
+            poster_dir: xdg_dir("XDG_CACHE_HOME", ".cache").join("gativideo/posters"),
         }
     }
 }
 
+impl Config {
+    pub fn page_cache(&self) -> PageCache {
+        PageCache::new(self.yts_cache_dir.clone(), self.yts_cache_ttl)
+    }
+}
+
+/// XDG base directory from its variable, or its default under HOME
+fn xdg_dir(variable: &str, default: &str) -> PathBuf {
+    match env::var_os(variable) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME").expect("No HOME variable set.")).join(default),
+    }
+}
+
 pub fn configuration() -> anyhow::Result<Config> {
     let home_path = env::var_os("HOME").expect("No HOME variable set.");
 
@@ -0,0 +1,822 @@
+use std::{collections::HashMap, fmt};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use crate::elements::Focus;
+
+/// Focus dependent group of bindings. Named as the tables of the [keys] config section
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyContext {
+    Search,
+    Movies,
+    Downloads,
+    Torrents,
+    Subtitles,
+    Notification,
+    Loading,
+    Help,
+    History,
+    Genres,
+    Completion,
+    Page,
+    Watchlist,
+    Note,
+}
+
+impl KeyContext {
+    pub const ALL: [KeyContext; 14] = [
+        KeyContext::Search,
+        KeyContext::Movies,
+        KeyContext::Downloads,
+        KeyContext::Torrents,
+        KeyContext::Subtitles,
+        KeyContext::Notification,
+        KeyContext::Loading,
+        KeyContext::Help,
+        KeyContext::History,
+        KeyContext::Genres,
+        KeyContext::Completion,
+        KeyContext::Page,
+        KeyContext::Watchlist,
+        KeyContext::Note,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyContext::Search => "search",
+            KeyContext::Movies => "movies",
+            KeyContext::Downloads => "downloads",
+            KeyContext::Torrents => "torrents",
+            KeyContext::Subtitles => "subtitles",
+            KeyContext::Notification => "notification",
+            KeyContext::Loading => "loading",
+            KeyContext::Help => "help",
+            KeyContext::History => "history",
+            KeyContext::Genres => "genres",
+            KeyContext::Completion => "completion",
+            KeyContext::Page => "page",
+            KeyContext::Watchlist => "watchlist",
+            KeyContext::Note => "note",
+        }
+    }
+}
+
+impl From<&Focus> for KeyContext {
+    fn from(focus: &Focus) -> Self {
+        match focus {
+            Focus::InputBox => KeyContext::Search,
+            Focus::MovieTable => KeyContext::Movies,
+            Focus::TorrentTable => KeyContext::Downloads,
+            Focus::PopupTorrent => KeyContext::Torrents,
+            Focus::PopupSubtitle => KeyContext::Subtitles,
+            Focus::PopupNotification => KeyContext::Notification,
+            Focus::PopupGenre => KeyContext::Genres,
+            Focus::PopupPage => KeyContext::Page,
+            Focus::Watchlist => KeyContext::Watchlist,
+            Focus::PopupNote => KeyContext::Note,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyAction {
+    Quit,
+    FocusNext,
+    Search,
+    Complete,
+    DeleteChar,
+    DeleteNextChar,
+    DeleteWord,
+    DeleteLine,
+    CursorLeft,
+    CursorRight,
+    LineStart,
+    LineEnd,
+    HistoryPrevious,
+    HistoryNext,
+    HistorySearch,
+    Down,
+    Up,
+    First,
+    Last,
+    NextPage,
+    PreviousPage,
+    FirstPage,
+    LastPage,
+    GoToPage,
+    Refresh,
+    Torrents,
+    Subtitles,
+    Genres,
+    Details,
+    Sort,
+    Watch,
+    Watchlist,
+    Note,
+    Toggle,
+    Remove,
+    Select,
+    Close,
+    Cancel,
+    Help,
+}
+
+impl KeyAction {
+    const ALL: [KeyAction; 39] = [
+        KeyAction::Quit,
+        KeyAction::FocusNext,
+        KeyAction::Search,
+        KeyAction::Complete,
+        KeyAction::DeleteChar,
+        KeyAction::DeleteNextChar,
+        KeyAction::DeleteWord,
+        KeyAction::DeleteLine,
+        KeyAction::CursorLeft,
+        KeyAction::CursorRight,
+        KeyAction::LineStart,
+        KeyAction::LineEnd,
+        KeyAction::HistoryPrevious,
+        KeyAction::HistoryNext,
+        KeyAction::HistorySearch,
+        KeyAction::Down,
+        KeyAction::Up,
+        KeyAction::First,
+        KeyAction::Last,
+        KeyAction::NextPage,
+        KeyAction::PreviousPage,
+        KeyAction::FirstPage,
+        KeyAction::LastPage,
+        KeyAction::GoToPage,
+        KeyAction::Refresh,
+        KeyAction::Torrents,
+        KeyAction::Subtitles,
+        KeyAction::Genres,
+        KeyAction::Details,
+        KeyAction::Sort,
+        KeyAction::Watch,
+        KeyAction::Watchlist,
+        KeyAction::Note,
+        KeyAction::Toggle,
+        KeyAction::Remove,
+        KeyAction::Select,
+        KeyAction::Close,
+        KeyAction::Cancel,
+        KeyAction::Help,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyAction::Quit => "quit",
+            KeyAction::FocusNext => "focus_next",
+            KeyAction::Search => "search",
+            KeyAction::Complete => "complete",
+            KeyAction::DeleteChar => "delete_char",
+            KeyAction::DeleteNextChar => "delete_next_char",
+            KeyAction::DeleteWord => "delete_word",
+            KeyAction::DeleteLine => "delete_line",
+            KeyAction::CursorLeft => "cursor_left",
+            KeyAction::CursorRight => "cursor_right",
+            KeyAction::LineStart => "line_start",
+            KeyAction::LineEnd => "line_end",
+            KeyAction::HistoryPrevious => "history_previous",
+            KeyAction::HistoryNext => "history_next",
+            KeyAction::HistorySearch => "history_search",
+            KeyAction::Down => "down",
+            KeyAction::Up => "up",
+            KeyAction::First => "first",
+            KeyAction::Last => "last",
+            KeyAction::NextPage => "next_page",
+            KeyAction::PreviousPage => "previous_page",
+            KeyAction::FirstPage => "first_page",
+            KeyAction::LastPage => "last_page",
+            KeyAction::GoToPage => "go_to_page",
+            KeyAction::Refresh => "refresh",
+            KeyAction::Torrents => "torrents",
+            KeyAction::Subtitles => "subtitles",
+            KeyAction::Genres => "genres",
+            KeyAction::Details => "details",
+            KeyAction::Sort => "sort",
+            KeyAction::Watch => "watch",
+            KeyAction::Watchlist => "watchlist",
+            KeyAction::Note => "note",
+            KeyAction::Toggle => "toggle",
+            KeyAction::Remove => "remove",
+            KeyAction::Select => "select",
+            KeyAction::Close => "close",
+            KeyAction::Cancel => "cancel",
+            KeyAction::Help => "help",
+        }
+    }
+
+    /// Text of the action in the help popup
+    pub fn description(&self, context: KeyContext) -> &'static str {
+        match (self, context) {
+            (KeyAction::Quit, _) => "Quit gativideo",
+            (KeyAction::FocusNext, _) => "Move focus to the next panel",
+            (KeyAction::Search, _) => {
+                "Search movies (filters: year: rating: order: genre: quality:)"
+            }
+            (KeyAction::Complete, KeyContext::Completion) => "Select the next completion",
+            (KeyAction::Complete, _) => "Complete a filter or value, else move focus",
+            (KeyAction::DeleteChar, _) => "Delete the character before the cursor",
+            (KeyAction::DeleteNextChar, _) => "Delete the character under the cursor",
+            (KeyAction::DeleteWord, _) => "Delete the word before the cursor",
+            (KeyAction::DeleteLine, _) => "Delete everything before the cursor",
+            (KeyAction::CursorLeft, _) => "Move the cursor left",
+            (KeyAction::CursorRight, _) => "Move the cursor right",
+            (KeyAction::LineStart, _) => "Move the cursor to the start",
+            (KeyAction::LineEnd, _) => "Move the cursor to the end",
+            (KeyAction::HistoryPrevious, _) => "Previous search of the history",
+            (KeyAction::HistoryNext, _) => "Next search of the history",
+            (KeyAction::HistorySearch, KeyContext::History) => "Older search with the same text",
+            (KeyAction::HistorySearch, _) => "Search the history backwards",
+            (KeyAction::Down, _) => "Select next row",
+            (KeyAction::Up, _) => "Select previous row",
+            (KeyAction::First, _) => "Select first row",
+            (KeyAction::Last, _) => "Select last row",
+            (KeyAction::NextPage, _) => "Next page of movies",
+            (KeyAction::PreviousPage, _) => "Previous page of movies",
+            (KeyAction::FirstPage, _) => "First page of movies",
+            (KeyAction::LastPage, _) => "Last page of movies",
+            (KeyAction::GoToPage, _) => "Go to a page of movies by its number",
+            (KeyAction::Refresh, _) => "Search the page again, skipping the cache",
+            (KeyAction::Torrents, _) => "Show the torrents of the selected movie",
+            (KeyAction::Subtitles, _) => "Show the subtitles of the selected movie",
+            (KeyAction::Genres, _) => "Pick the genre filter of the search",
+            (KeyAction::Details, _) => "Show or hide the details of the selected movie",
+            (KeyAction::Sort, KeyContext::Movies) => "Sort the page by the next column",
+            (KeyAction::Sort, _) => "Sort by the next column",
+            (KeyAction::Watch, _) => "Add the selected movie to the watchlist",
+            (KeyAction::Watchlist, _) => "Show or hide the watchlist",
+            (KeyAction::Note, _) => "Write a note for the selected movie",
+            (KeyAction::Toggle, _) => "Start or stop the selected download",
+            (KeyAction::Remove, KeyContext::Watchlist) => "Remove the movie from the watchlist",
+            (KeyAction::Remove, _) => "Remove the selected download",
+            (KeyAction::Select, KeyContext::Torrents) => "Add the torrent to Transmission",
+            (KeyAction::Select, KeyContext::Subtitles) => "Download the subtitle",
+            (KeyAction::Select, KeyContext::History) => "Search the found query",
+            (KeyAction::Select, KeyContext::Genres) => "Search movies of the genre",
+            (KeyAction::Select, KeyContext::Completion) => "Use the selected completion",
+            (KeyAction::Select, KeyContext::Page) => "Go to the typed page",
+            (KeyAction::Select, KeyContext::Note) => "Save the note",
+            (KeyAction::Select, _) => "Select",
+            (KeyAction::Close, KeyContext::Watchlist) => "Back to the movies",
+            (KeyAction::Close, KeyContext::Note) => "Close without saving the note",
+            (KeyAction::Close, _) => "Close the popup",
+            (KeyAction::Cancel, KeyContext::History) => "Back to the query before searching",
+            (KeyAction::Cancel, _) => "Cancel the running request",
+            (KeyAction::Help, _) => "Show the keys of the focused panel",
+        }
+    }
+
+    /// Whether the action is shown in the hint bar. Moving around is left to the help popup
+    pub fn is_hint(&self) -> bool {
+        !matches!(
+            self,
+            KeyAction::DeleteChar
+                | KeyAction::DeleteNextChar
+                | KeyAction::DeleteWord
+                | KeyAction::DeleteLine
+                | KeyAction::CursorLeft
+                | KeyAction::CursorRight
+                | KeyAction::LineStart
+                | KeyAction::LineEnd
+                | KeyAction::HistoryPrevious
+                | KeyAction::HistoryNext
+                | KeyAction::Down
+                | KeyAction::Up
+                | KeyAction::First
+                | KeyAction::Last
+                | KeyAction::PreviousPage
+                | KeyAction::FirstPage
+                | KeyAction::LastPage
+        )
+    }
+}
+
+impl TryFrom<&str> for KeyAction {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        KeyAction::ALL
+            .into_iter()
+            .find(|action| action.name() == value)
+            .ok_or_else(|| format!("unknown action '{value}'"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // Shift is already part of the char (ex: 'G') and of BackTab, "shift-g" is 'G' too
+        let (code, modifiers) = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => (
+                KeyCode::Char(c.to_uppercase().next().unwrap_or(c)),
+                modifiers - KeyModifiers::SHIFT,
+            ),
+            KeyCode::Char(_) | KeyCode::BackTab => (code, modifiers - KeyModifiers::SHIFT),
+            _ => (code, modifiers),
+        };
+
+        Self { code, modifiers }
+    }
+
+    fn plain(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+
+    /// Printable key without ctrl/alt, which would be typed in the search box
+    pub fn is_text(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_))
+            && !self
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    }
+}
+
+impl From<KeyEvent> for KeyChord {
+    fn from(key: KeyEvent) -> Self {
+        KeyChord::new(key.code, key.modifiers)
+    }
+}
+
+impl TryFrom<&str> for KeyChord {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut key = value;
+
+        // A single char is always a key, so "-" can be bound
+        while key.chars().count() > 1
+            && let Some((modifier, rest)) = key.split_once('-')
+        {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "c" => KeyModifiers::CONTROL,
+                "alt" | "a" | "m" => KeyModifiers::ALT,
+                "shift" | "s" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier '{modifier}' in '{value}'")),
+            };
+            key = rest;
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                f if f.starts_with('f') && f[1..].parse::<u8>().is_ok_and(|n| n > 0) => {
+                    KeyCode::F(f[1..].parse().unwrap_or(1))
+                }
+                _ => return Err(format!("unknown key '{value}'")),
+            },
+        };
+
+        // The shifted char of "shift-1" depends on the keyboard, it is bound as "!"
+        if let KeyCode::Char(c) = code
+            && !c.is_alphabetic()
+            && modifiers.contains(KeyModifiers::SHIFT)
+        {
+            return Err(format!("'{value}' needs the shifted char instead of shift"));
+        }
+
+        Ok(KeyChord::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift-")?;
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::Enter => write!(f, "enter"),
+            KeyCode::Esc => write!(f, "esc"),
+            KeyCode::Tab => write!(f, "tab"),
+            KeyCode::BackTab => write!(f, "shift-tab"),
+            KeyCode::Backspace => write!(f, "backspace"),
+            KeyCode::Delete => write!(f, "delete"),
+            KeyCode::Insert => write!(f, "insert"),
+            KeyCode::Up => write!(f, "up"),
+            KeyCode::Down => write!(f, "down"),
+            KeyCode::Left => write!(f, "left"),
+            KeyCode::Right => write!(f, "right"),
+            KeyCode::Home => write!(f, "home"),
+            KeyCode::End => write!(f, "end"),
+            KeyCode::PageUp => write!(f, "pageup"),
+            KeyCode::PageDown => write!(f, "pagedown"),
+            KeyCode::F(n) => write!(f, "f{n}"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// Value of an action in the [keys] config section: one key or a list of keys
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum KeysToml {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    bindings: HashMap<KeyContext, Vec<(KeyAction, Vec<KeyChord>)>>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        use KeyCode::*;
+        use KeyContext as C;
+
+        let bind = |action: KeyAction, codes: &[KeyCode]| {
+            (action, codes.iter().map(|&c| KeyChord::plain(c)).collect())
+        };
+
+        // Plain key plus an emacs style ctrl key
+        let bind_ctrl = |action: KeyAction, codes: &[KeyCode], ctrl: char| {
+            let (action, mut chords): (KeyAction, Vec<KeyChord>) = bind(action, codes);
+            chords.push(KeyChord::new(Char(ctrl), KeyModifiers::CONTROL));
+            (action, chords)
+        };
+
+        let bindings = HashMap::from([
+            (
+                C::Search,
+                vec![
+                    bind(KeyAction::Search, &[Enter]),
+                    bind(KeyAction::Complete, &[Tab]),
+                    bind(KeyAction::FocusNext, &[BackTab]),
+                    bind(KeyAction::DeleteChar, &[Backspace]),
+                    bind(KeyAction::DeleteNextChar, &[Delete]),
+                    bind_ctrl(KeyAction::DeleteWord, &[], 'w'),
+                    bind_ctrl(KeyAction::DeleteLine, &[], 'u'),
+                    bind(KeyAction::CursorLeft, &[Left]),
+                    bind(KeyAction::CursorRight, &[Right]),
+                    bind_ctrl(KeyAction::LineStart, &[Home], 'a'),
+                    bind_ctrl(KeyAction::LineEnd, &[End], 'e'),
+                    bind(KeyAction::HistoryPrevious, &[Up]),
+                    bind(KeyAction::HistoryNext, &[Down]),
+                    bind_ctrl(KeyAction::HistorySearch, &[], 'r'),
+                    bind_ctrl(KeyAction::Genres, &[], 'g'),
+                    bind(KeyAction::Help, &[F(1)]),
+                    bind(KeyAction::Quit, &[Esc]),
+                ],
+            ),
+            (
+                C::Movies,
+                vec![
+                    bind(KeyAction::Down, &[Char('j'), Down]),
+                    bind(KeyAction::Up, &[Char('k'), Up]),
+                    bind(KeyAction::NextPage, &[Char('l'), Right]),
+                    bind(KeyAction::PreviousPage, &[Char('h'), Left]),
+                    bind(KeyAction::FirstPage, &[Char('H')]),
+                    bind(KeyAction::LastPage, &[Char('L')]),
+                    bind(KeyAction::GoToPage, &[Char(':')]),
+                    bind(KeyAction::Refresh, &[Char('r')]),
+                    bind(KeyAction::First, &[Char('g')]),
+                    bind(KeyAction::Last, &[Char('G')]),
+                    bind(KeyAction::Torrents, &[Char('t')]),
+                    bind(KeyAction::Subtitles, &[Char('s')]),
+                    bind(KeyAction::Genres, &[Char('f')]),
+                    bind(KeyAction::Details, &[Char('i')]),
+                    bind(KeyAction::Sort, &[Char('o')]),
+                    bind(KeyAction::Watch, &[Char('w')]),
+                    bind(KeyAction::Watchlist, &[Char('W')]),
+                    bind(KeyAction::FocusNext, &[Tab]),
+                    bind(KeyAction::Help, &[Char('?'), F(1)]),
+                    bind(KeyAction::Quit, &[Char('q'), Esc]),
+                ],
+            ),
+            (
+                C::Downloads,
+                vec![
+                    bind(KeyAction::Down, &[Char('j'), Down]),
+                    bind(KeyAction::Up, &[Char('k'), Up]),
+                    bind(KeyAction::Toggle, &[Char('s')]),
+                    bind(KeyAction::Remove, &[Char('d')]),
+                    bind(KeyAction::FocusNext, &[Tab]),
+                    bind(KeyAction::Help, &[Char('?'), F(1)]),
+                    bind(KeyAction::Quit, &[Char('q'), Esc]),
+                ],
+            ),
+            (
+                C::Torrents,
+                vec![
+                    bind(KeyAction::Down, &[Char('j'), Down]),
+                    bind(KeyAction::Up, &[Char('k'), Up]),
+                    bind(KeyAction::Select, &[Enter]),
+                    bind(KeyAction::Sort, &[Char('o')]),
+                    bind(KeyAction::Help, &[Char('?'), F(1)]),
+                    bind(KeyAction::Close, &[Char('q'), Esc]),
+                ],
+            ),
+            (
+                C::Subtitles,
+                vec![
+                    bind(KeyAction::Down, &[Char('j'), Down]),
+                    bind(KeyAction::Up, &[Char('k'), Up]),
+                    bind(KeyAction::Select, &[Enter]),
+                    bind(KeyAction::Sort, &[Char('o')]),
+                    bind(KeyAction::Help, &[Char('?'), F(1)]),
+                    bind(KeyAction::Close, &[Char('q'), Esc]),
+                ],
+            ),
+            (
+                C::Completion,
+                vec![
+                    bind(KeyAction::Complete, &[Tab]),
+                    bind_ctrl(KeyAction::Down, &[Down], 'n'),
+                    bind_ctrl(KeyAction::Up, &[Up, BackTab], 'p'),
+                    bind(KeyAction::Select, &[Enter]),
+                    bind(KeyAction::DeleteChar, &[Backspace]),
+                    bind(KeyAction::Help, &[F(1)]),
+                    bind(KeyAction::Close, &[Esc]),
+                ],
+            ),
+            (
+                C::Genres,
+                vec![
+                    bind(KeyAction::Down, &[Char('j'), Down]),
+                    bind(KeyAction::Up, &[Char('k'), Up]),
+                    bind(KeyAction::Select, &[Enter]),
+                    bind(KeyAction::Help, &[Char('?'), F(1)]),
+                    bind(KeyAction::Close, &[Char('q'), Esc]),
+                ],
+            ),
+            (
+                C::Page,
+                vec![
+                    bind(KeyAction::Select, &[Enter]),
+                    bind(KeyAction::DeleteChar, &[Backspace]),
+                    bind(KeyAction::Help, &[F(1)]),
+                    bind(KeyAction::Close, &[Esc]),
+                ],
+            ),
+            (
+                C::Watchlist,
+                vec![
+                    bind(KeyAction::Down, &[Char('j'), Down]),
+                    bind(KeyAction::Up, &[Char('k'), Up]),
+                    bind(KeyAction::First, &[Char('g')]),
+                    bind(KeyAction::Last, &[Char('G')]),
+                    bind(KeyAction::Torrents, &[Char('t')]),
+                    bind(KeyAction::Subtitles, &[Char('s')]),
+                    bind(KeyAction::Note, &[Char('n')]),
+                    bind(KeyAction::Remove, &[Char('d')]),
+                    bind(KeyAction::FocusNext, &[Tab]),
+                    bind(KeyAction::Help, &[Char('?'), F(1)]),
+                    bind(KeyAction::Close, &[Char('W'), Char('q'), Esc]),
+                ],
+            ),
+            (
+                C::Note,
+                vec![
+                    bind(KeyAction::Select, &[Enter]),
+                    bind(KeyAction::DeleteChar, &[Backspace]),
+                    bind(KeyAction::DeleteNextChar, &[Delete]),
+                    bind_ctrl(KeyAction::DeleteWord, &[], 'w'),
+                    bind_ctrl(KeyAction::DeleteLine, &[], 'u'),
+                    bind(KeyAction::CursorLeft, &[Left]),
+                    bind(KeyAction::CursorRight, &[Right]),
+                    bind_ctrl(KeyAction::LineStart, &[Home], 'a'),
+                    bind_ctrl(KeyAction::LineEnd, &[End], 'e'),
+                    bind(KeyAction::Help, &[F(1)]),
+                    bind(KeyAction::Close, &[Esc]),
+                ],
+            ),
+            (
+                C::Notification,
+                vec![bind(KeyAction::Close, &[Char('q'), Esc])],
+            ),
+            (C::Loading, vec![bind(KeyAction::Cancel, &[Esc])]),
+            (
+                C::History,
+                vec![
+                    bind_ctrl(KeyAction::HistorySearch, &[], 'r'),
+                    bind(KeyAction::DeleteChar, &[Backspace]),
+                    bind(KeyAction::Select, &[Enter]),
+                    bind(KeyAction::Cancel, &[Esc]),
+                ],
+            ),
+            (
+                C::Help,
+                vec![bind(KeyAction::Close, &[Char('q'), Esc, Char('?'), F(1)])],
+            ),
+        ]);
+
+        Self { bindings }
+    }
+}
+
+impl KeyMap {
+    /// Default bindings overridden by the [keys] config section.
+    /// Each configured action replaces all the default keys of that action in its context.
+    pub fn new(keys: HashMap<String, HashMap<String, KeysToml>>) -> Result<KeyMap, String> {
+        let mut keymap = KeyMap::default();
+
+        for (context_name, actions) in keys {
+            let context = KeyContext::ALL
+                .into_iter()
+                .find(|c| c.name() == context_name)
+                .ok_or_else(|| format!("unknown context [keys.{context_name}]"))?;
+
+            for (action_name, keys) in actions {
+                let action = KeyAction::try_from(action_name.as_str())
+                    .map_err(|e| format!("{e} in [keys.{context_name}]"))?;
+
+                let keys = match keys {
+                    KeysToml::One(key) => vec![key],
+                    KeysToml::Many(keys) => keys,
+                };
+
+                let chords = keys
+                    .iter()
+                    .map(|key| KeyChord::try_from(key.as_str()))
+                    .collect::<Result<Vec<KeyChord>, String>>()
+                    .map_err(|e| format!("{e} in [keys.{context_name}]"))?;
+
+                // Only the actions of the defaults are handled in each context
+                let bindings = keymap.bindings.entry(context).or_default();
+                match bindings.iter_mut().find(|(a, _)| *a == action) {
+                    Some((_, current)) => *current = chords,
+                    None => {
+                        return Err(format!(
+                            "'{action_name}' is not an action of [keys.{context_name}]"
+                        ));
+                    }
+                }
+            }
+        }
+
+        keymap.validate()?;
+
+        Ok(keymap)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (context, bindings) in &self.bindings {
+            let mut used: HashMap<KeyChord, KeyAction> = HashMap::new();
+
+            for (action, chords) in bindings {
+                for chord in chords {
+                    if matches!(
+                        context,
+                        KeyContext::Search
+                            | KeyContext::History
+                            | KeyContext::Completion
+                            | KeyContext::Page
+                            | KeyContext::Note
+                    ) && chord.is_text()
+                    {
+                        return Err(format!(
+                            "'{chord}' can not be bound to '{}' in [keys.{}], it is needed to type",
+                            action.name(),
+                            context.name()
+                        ));
+                    }
+
+                    if let Some(other) = used.insert(*chord, *action)
+                        && other != *action
+                    {
+                        return Err(format!(
+                            "'{chord}' is bound to both '{}' and '{}' in [keys.{}]",
+                            other.name(),
+                            action.name(),
+                            context.name()
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Actions and keys of a context, in the order they were defined
+    pub fn bindings(&self, context: KeyContext) -> &[(KeyAction, Vec<KeyChord>)] {
+        self.bindings
+            .get(&context)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn action(&self, context: KeyContext, key: KeyEvent) -> Option<KeyAction> {
+        let chord = KeyChord::from(key);
+
+        self.bindings.get(&context).and_then(|bindings| {
+            bindings
+                .iter()
+                .find(|(_, chords)| chords.contains(&chord))
+                .map(|(action, _)| *action)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::config::{KeyAction, KeyChord, KeyContext, KeyMap, KeysToml};
+
+    fn keys(
+        context: &str,
+        action: &str,
+        keys: &[&str],
+    ) -> HashMap<String, HashMap<String, KeysToml>> {
+        HashMap::from([(
+            context.to_string(),
+            HashMap::from([(
+                action.to_string(),
+                KeysToml::Many(keys.iter().map(|k| k.to_string()).collect()),
+            )]),
+        )])
+    }
+
+    #[test]
+    fn parse_chords() {
+        assert_eq!(
+            KeyChord::try_from("ctrl-r").unwrap(),
+            KeyChord::new(KeyCode::Char('r'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            KeyChord::try_from("G").unwrap(),
+            KeyChord::from(KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT))
+        );
+        assert_eq!(
+            KeyChord::try_from("shift-tab").unwrap().code,
+            KeyCode::BackTab
+        );
+        assert_eq!(
+            KeyChord::try_from("shift-a").unwrap(),
+            KeyChord::try_from("A").unwrap()
+        );
+        assert!(KeyChord::try_from("shift-1").is_err());
+        assert_eq!(KeyChord::try_from("-").unwrap().code, KeyCode::Char('-'));
+        assert!(KeyChord::try_from("hyper-x").is_err());
+        assert!(KeyChord::try_from("enterr").is_err());
+    }
+
+    #[test]
+    fn override_replaces_default_keys() {
+        let keymap = KeyMap::new(keys("movies", "subtitles", &["S"])).unwrap();
+        let s = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE);
+        let shift_s = KeyEvent::new(KeyCode::Char('S'), KeyModifiers::SHIFT);
+
+        assert_eq!(keymap.action(KeyContext::Movies, s), None);
+        assert_eq!(
+            keymap.action(KeyContext::Movies, shift_s),
+            Some(KeyAction::Subtitles)
+        );
+        assert_eq!(
+            keymap.action(KeyContext::Downloads, s),
+            Some(KeyAction::Toggle)
+        );
+    }
+
+    #[test]
+    fn conflicts_are_rejected() {
+        assert!(KeyMap::new(keys("downloads", "remove", &["s"])).is_err());
+        assert!(KeyMap::new(keys("search", "search", &["x"])).is_err());
+        assert!(KeyMap::new(keys("search", "search", &["ctrl-x"])).is_ok());
+        assert!(KeyMap::new(keys("moviez", "quit", &["x"])).is_err());
+        assert!(KeyMap::new(keys("movies", "jump", &["x"])).is_err());
+        assert_eq!(
+            KeyMap::new(keys("downloads", "torrents", &["t"])).unwrap_err(),
+            "'torrents' is not an action of [keys.downloads]"
+        );
+    }
+}
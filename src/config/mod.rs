@@ -1,3 +1,7 @@
 mod config_toml;
+mod keys;
+mod theme;
 
 pub use config_toml::*;
+pub use keys::*;
+pub use theme::*;
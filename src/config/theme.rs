@@ -0,0 +1,320 @@
+use std::str::FromStr;
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    symbols::{border, scrollbar},
+    widgets::{Block, Borders, Scrollbar, ScrollbarOrientation},
+};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ThemeToml {
+    pub preset: Option<String>,
+    pub text: Option<String>,
+    pub input: Option<String>,
+    pub title: Option<String>,
+    pub header: Option<String>,
+    pub border: Option<String>,
+    pub border_focused: Option<String>,
+    pub highlight_fg: Option<String>,
+    pub highlight_bg: Option<String>,
+    pub success: Option<String>,
+    pub warning: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Icons {
+    pub highlight: &'static str,
+    pub popup_highlight: &'static str,
+    pub error: &'static str,
+    pub success: &'static str,
+    pub stalled: &'static str,
+    pub downloading: &'static str,
+    pub transmission: &'static str,
+    /// Direction of the sorted column
+    pub ascending: &'static str,
+    pub descending: &'static str,
+    /// Page controls of the movies footer: first, previous, next and last
+    pub pages: [&'static str; 4],
+    pub spinner: &'static [&'static str],
+}
+
+impl Icons {
+    const NERD_FONT: Icons = Icons {
+        highlight: "  ",
+        popup_highlight: " ",
+        error: "",
+        success: "󰸞",
+        stalled: "",
+        downloading: "",
+        transmission: "󱘖",
+        ascending: "▲",
+        descending: "▼",
+        pages: ["«", "‹", "›", "»"],
+        spinner: &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
+    };
+
+    const ASCII: Icons = Icons {
+        highlight: " > ",
+        popup_highlight: ">",
+        error: "[!]",
+        success: "[ok]",
+        stalled: "!",
+        downloading: "v",
+        transmission: "*",
+        ascending: "^",
+        descending: "v",
+        pages: ["<<", "<", ">", ">>"],
+        spinner: &["|", "/", "-", "\\"],
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorderStyle {
+    Unicode,
+    Ascii,
+}
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub text: Color,
+    pub input: Color,
+    pub title: Color,
+    pub header: Color,
+    pub border: Color,
+    pub border_focused: Color,
+    pub highlight_fg: Color,
+    pub highlight_bg: Color,
+    pub success: Color,
+    pub warning: Color,
+    pub error: Color,
+    pub icons: Icons,
+    pub borders: BorderStyle,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub const PRESETS: [&'static str; 4] = ["dark", "light", "high-contrast", "ascii"];
+
+    pub fn dark() -> Theme {
+        Self {
+            text: Color::White,
+            input: Color::Gray,
+            title: Color::White,
+            header: Color::DarkGray,
+            border: Color::DarkGray,
+            border_focused: Color::Gray,
+            highlight_fg: Color::Black,
+            highlight_bg: Color::DarkGray,
+            success: Color::Green,
+            warning: Color::Yellow,
+            error: Color::Red,
+            icons: Icons::NERD_FONT,
+            borders: BorderStyle::Unicode,
+        }
+    }
+
+    pub fn light() -> Theme {
+        Self {
+            text: Color::Black,
+            input: Color::Black,
+            title: Color::Black,
+            header: Color::DarkGray,
+            border: Color::Gray,
+            border_focused: Color::Black,
+            highlight_fg: Color::Black,
+            highlight_bg: Color::Gray,
+            success: Color::Green,
+            warning: Color::Rgb(175, 95, 0),
+            error: Color::Red,
+            ..Self::dark()
+        }
+    }
+
+    pub fn high_contrast() -> Theme {
+        Self {
+            text: Color::White,
+            input: Color::White,
+            title: Color::White,
+            header: Color::Cyan,
+            border: Color::White,
+            border_focused: Color::Yellow,
+            highlight_fg: Color::Black,
+            highlight_bg: Color::Yellow,
+            success: Color::LightGreen,
+            warning: Color::LightYellow,
+            error: Color::LightRed,
+            ..Self::dark()
+        }
+    }
+
+    pub fn ascii() -> Theme {
+        Self {
+            icons: Icons::ASCII,
+            borders: BorderStyle::Ascii,
+            ..Self::dark()
+        }
+    }
+
+    pub fn new(toml: ThemeToml) -> Result<Theme, String> {
+        let mut theme = match toml.preset.as_deref().unwrap_or("dark") {
+            "dark" => Self::dark(),
+            "light" => Self::light(),
+            "high-contrast" => Self::high_contrast(),
+            "ascii" => Self::ascii(),
+            preset => {
+                return Err(format!(
+                    "unknown preset '{preset}', it could be {}",
+                    Self::PRESETS.join(", ")
+                ));
+            }
+        };
+
+        let colors = [
+            (&mut theme.text, toml.text),
+            (&mut theme.input, toml.input),
+            (&mut theme.title, toml.title),
+            (&mut theme.header, toml.header),
+            (&mut theme.border, toml.border),
+            (&mut theme.border_focused, toml.border_focused),
+            (&mut theme.highlight_fg, toml.highlight_fg),
+            (&mut theme.highlight_bg, toml.highlight_bg),
+            (&mut theme.success, toml.success),
+            (&mut theme.warning, toml.warning),
+            (&mut theme.error, toml.error),
+        ];
+
+        for (color, value) in colors {
+            if let Some(value) = value {
+                *color = Color::from_str(&value).map_err(|_| format!("unknown color '{value}'"))?;
+            }
+        }
+
+        Ok(theme)
+    }
+
+    pub fn text_style(&self) -> Style {
+        Style::default().fg(self.text)
+    }
+
+    pub fn title_style(&self) -> Style {
+        Style::new().fg(self.title).bold()
+    }
+
+    pub fn header_style(&self) -> Style {
+        Style::new().fg(self.header).bold()
+    }
+
+    pub fn border_style(&self, focused: bool) -> Style {
+        if focused {
+            Style::default()
+                .fg(self.border_focused)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(self.border)
+        }
+    }
+
+    pub fn highlight_style(&self) -> Style {
+        Style::default()
+            .bg(self.highlight_bg)
+            .fg(self.highlight_fg)
+            .add_modifier(Modifier::BOLD)
+    }
+
+    /// Block of the main panels (search box, movies and downloads)
+    pub fn panel_block(&self, focused: bool) -> Block<'static> {
+        let set = match self.borders {
+            BorderStyle::Unicode => border::THICK,
+            BorderStyle::Ascii => Self::ASCII_BORDER,
+        };
+
+        Block::default()
+            .borders(Borders::ALL)
+            .border_set(set)
+            .border_style(self.border_style(focused))
+    }
+
+    pub fn popup_block(&self) -> Block<'static> {
+        let set = match self.borders {
+            BorderStyle::Unicode => border::PLAIN,
+            BorderStyle::Ascii => Self::ASCII_BORDER,
+        };
+
+        Block::default().borders(Borders::ALL).border_set(set)
+    }
+
+    pub fn scrollbar(&self) -> Scrollbar<'static> {
+        let symbols = match self.borders {
+            BorderStyle::Unicode => scrollbar::VERTICAL,
+            BorderStyle::Ascii => Self::ASCII_SCROLLBAR,
+        };
+
+        Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .symbols(symbols)
+            .begin_symbol(None)
+            .track_symbol(None)
+            .end_symbol(None)
+    }
+
+    const ASCII_BORDER: border::Set<'static> = border::Set {
+        top_left: "+",
+        top_right: "+",
+        bottom_left: "+",
+        bottom_right: "+",
+        vertical_left: "|",
+        vertical_right: "|",
+        horizontal_top: "-",
+        horizontal_bottom: "-",
+    };
+
+    const ASCII_SCROLLBAR: scrollbar::Set<'static> = scrollbar::Set {
+        track: "|",
+        thumb: "#",
+        begin: "^",
+        end: "v",
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preset_with_color_overrides() {
+        let toml = ThemeToml {
+            preset: Some("ascii".to_string()),
+            border_focused: Some("yellow".to_string()),
+            highlight_bg: Some("#3a3a3a".to_string()),
+            ..Default::default()
+        };
+
+        let theme = Theme::new(toml).unwrap();
+        assert_eq!(theme.borders, BorderStyle::Ascii);
+        assert_eq!(theme.icons.error, "[!]");
+        assert_eq!(theme.border_focused, Color::Yellow);
+        assert_eq!(theme.highlight_bg, Color::Rgb(0x3a, 0x3a, 0x3a));
+        assert_eq!(theme.text, Theme::dark().text);
+    }
+
+    #[test]
+    fn invalid_theme_values() {
+        let preset = ThemeToml {
+            preset: Some("solarized".to_string()),
+            ..Default::default()
+        };
+        assert!(Theme::new(preset).unwrap_err().contains("solarized"));
+
+        let color = ThemeToml {
+            error: Some("reddish".to_string()),
+            ..Default::default()
+        };
+        assert!(Theme::new(color).unwrap_err().contains("reddish"));
+    }
+}
@@ -1,7 +1,10 @@
+use std::{sync::Arc, time::Duration};
+
 use ratatui::{
     layout::Constraint,
-    style::{Color, Modifier, Style},
-    widgets::{Block, BorderType, Borders, Row, ScrollbarState, Table, TableState},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Row, ScrollbarState, Table, TableState},
 };
 use transmission_rpc::{
     TransClient,
@@ -11,13 +14,28 @@ use transmission_rpc::{
     },
 };
 
-use crate::elements::Focus;
+use tokio::{sync::Mutex, time::Instant};
+
+use crate::config::Theme;
+use crate::elements::{Column, Columns, Focus};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Connection {
+    Connecting,
+    Connected,
+    Disconnected {
+        error: String,
+        attempts: u32,
+        retry_at: Instant,
+    },
+}
 
 pub struct Transmission {
-    pub client: TransClient,
+    pub client: Arc<Mutex<TransClient>>,
     pub table_state: TableState,
     pub torrents: Vec<Torrent>,
     pub scroll_state: ScrollbarState,
+    pub connection: Connection,
     download_dir: String,
 }
 
@@ -40,34 +58,98 @@ impl Transmission {
         }
 
         Ok(Self {
-            client,
+            client: Arc::new(Mutex::new(client)),
             table_state,
             download_dir,
             scroll_state: ScrollbarState::default().position(1),
             torrents: Vec::new(),
+            connection: Connection::Connecting,
         })
     }
 
+    const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
     pub fn is_visible(&self) -> bool {
-        !self.torrents.is_empty()
+        !self.torrents.is_empty() || matches!(self.connection, Connection::Disconnected { .. })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        matches!(self.connection, Connection::Connected)
+    }
+
+    /// While disconnected the scans are delayed with an exponential backoff
+    pub fn should_scan(&self) -> bool {
+        match &self.connection {
+            Connection::Disconnected { retry_at, .. } => Instant::now() >= *retry_at,
+            _ => true,
+        }
+    }
+
+    pub fn connected(&mut self, torrents: Vec<Torrent>) {
+        self.torrents = torrents;
+        self.connection = Connection::Connected;
+    }
+
+    pub fn disconnected(&mut self, error: String) {
+        let attempts = match &self.connection {
+            Connection::Disconnected { attempts, .. } => attempts + 1,
+            _ => 1,
+        };
+
+        let backoff = Duration::from_secs(1 << attempts.min(6)).min(Self::MAX_BACKOFF);
+
+        self.torrents.clear();
+        self.table_state.select_first();
+        self.connection = Connection::Disconnected {
+            error,
+            attempts,
+            retry_at: Instant::now() + backoff,
+        };
+    }
+
+    pub fn status(&self, theme: &Theme) -> Line<'static> {
+        let icon = theme.icons.transmission;
+        let (text, color) = match &self.connection {
+            Connection::Connecting => (format!(" {icon} connecting "), theme.warning),
+            Connection::Connected => (format!(" {icon} transmission "), theme.success),
+            Connection::Disconnected { retry_at, .. } => (
+                format!(
+                    " {icon} disconnected, retry in {}s ",
+                    retry_at.saturating_duration_since(Instant::now()).as_secs()
+                ),
+                theme.error,
+            ),
+        };
+
+        Line::from(Span::styled(text, Style::default().fg(color)))
     }
 
-    pub async fn add(&mut self, torrent_url: &str) -> transmission_rpc::types::Result<bool> {
+    pub fn add(
+        &self,
+        torrent_url: &str,
+    ) -> impl Future<Output = transmission_rpc::types::Result<bool>> + Send + 'static {
+        let client = self.client.clone();
         let add: TorrentAddArgs = TorrentAddArgs {
             filename: Some(torrent_url.replace(" ", "%20").to_string()),
             download_dir: Some(self.download_dir.clone()),
             ..TorrentAddArgs::default()
         };
-        let res: RpcResponse<TorrentAddedOrDuplicate> = self.client.torrent_add(add).await?;
 
-        self.scan().await?;
+        async move {
+            let res: RpcResponse<TorrentAddedOrDuplicate> =
+                client.lock().await.torrent_add(add).await?;
 
-        Ok(res.is_ok())
+            Ok(res.is_ok())
+        }
     }
 
-    pub async fn toggle(&mut self, index: usize) -> transmission_rpc::types::Result<bool> {
+    pub fn toggle(
+        &self,
+        index: usize,
+    ) -> impl Future<Output = transmission_rpc::types::Result<bool>> + Send + 'static {
+        let client = self.client.clone();
         let torrent = &self.torrents[index];
-        let torrent_action = if matches!(torrent.status.as_ref().unwrap(), TorrentStatus::Stopped) {
+        let torrent_action = if matches!(torrent.status, Some(TorrentStatus::Stopped)) {
             TorrentAction::Start
         } else {
             TorrentAction::Stop
@@ -75,43 +157,74 @@ impl Transmission {
 
         let id = torrent.id().as_ref().unwrap().clone();
 
-        let res: RpcResponse<Nothing> =
-            self.client.torrent_action(torrent_action, vec![id]).await?;
+        async move {
+            let res: RpcResponse<Nothing> = client
+                .lock()
+                .await
+                .torrent_action(torrent_action, vec![id])
+                .await?;
 
-        Ok(res.is_ok())
+            Ok(res.is_ok())
+        }
     }
 
-    pub async fn remove(&mut self, index: usize) -> transmission_rpc::types::Result<bool> {
+    pub fn remove(
+        &self,
+        index: usize,
+    ) -> impl Future<Output = transmission_rpc::types::Result<bool>> + Send + 'static {
+        let client = self.client.clone();
         let torrent = &self.torrents[index];
         let id = torrent.id().as_ref().unwrap().clone();
 
-        let res: RpcResponse<Nothing> = self.client.torrent_remove(vec![id], false).await?;
-
-        Ok(res.is_ok())
-    }
-
-    pub async fn scan(&mut self) -> transmission_rpc::types::Result<()> {
-        let torrents = self
-            .client
-            .torrent_get(
-                Some(vec![
-                    TorrentGetField::Id,
-                    TorrentGetField::Name,
-                    TorrentGetField::PercentDone,
-                    TorrentGetField::SizeWhenDone,
-                    TorrentGetField::PeersSendingToUs,
-                    TorrentGetField::PeersConnected,
-                    TorrentGetField::IsStalled,
-                    TorrentGetField::Status,
-                ]),
-                None,
-            )
-            .await?;
+        async move {
+            let res: RpcResponse<Nothing> =
+                client.lock().await.torrent_remove(vec![id], false).await?;
 
-        self.torrents.clear();
-        self.torrents = torrents.arguments.torrents;
+            Ok(res.is_ok())
+        }
+    }
+
+    pub fn scan(
+        &self,
+    ) -> impl Future<Output = transmission_rpc::types::Result<Vec<Torrent>>> + Send + 'static {
+        let client = self.client.clone();
 
-        Ok(())
+        async move {
+            let torrents = client
+                .lock()
+                .await
+                .torrent_get(
+                    Some(vec![
+                        TorrentGetField::Id,
+                        TorrentGetField::Name,
+                        TorrentGetField::PercentDone,
+                        TorrentGetField::SizeWhenDone,
+                        TorrentGetField::PeersSendingToUs,
+                        TorrentGetField::PeersConnected,
+                        TorrentGetField::IsStalled,
+                        TorrentGetField::Status,
+                    ]),
+                    None,
+                )
+                .await?;
+
+            Ok(torrents.arguments.torrents)
+        }
+    }
+
+    pub fn torrent_status(torrent: &Torrent) -> &'static str {
+        if torrent.is_stalled.unwrap_or_default() {
+            "Stalled"
+        } else if torrent.percent_done.unwrap_or_default() == 1.0 {
+            "Finished"
+        } else {
+            "Downloading"
+        }
+    }
+
+    pub fn select(&mut self, row: usize) {
+        self.table_state.select(Some(row));
+        self.scroll_state = self.scroll_state.position(row);
     }
 
     pub fn scroll_bar_up(&mut self) {
@@ -123,33 +236,55 @@ impl Transmission {
 
     pub fn scroll_bar_down(&mut self) {
         let position = self.scroll_state.get_position();
-        if position < self.torrents.len() - 1 {
+        if position < self.torrents.len().saturating_sub(1) {
             self.scroll_state = self.scroll_state.position(position.saturating_add(1));
         }
     }
 
-    pub fn render(&mut self, focus: &Focus) -> (Table<'_>, u16) {
-        let widths = [
-            Constraint::Percentage(30),
-            Constraint::Percentage(10),
-            Constraint::Percentage(20),
-            Constraint::Percentage(20),
-            Constraint::Percentage(20),
-        ];
+    const COLUMNS: [Column; 5] = [
+        Column::new("Name", 30, 20, 0),
+        Column::new("Size", 10, 8, 3),
+        Column::new("Downloaded", 20, 8, 1),
+        Column::new("Status", 20, 14, 2),
+        Column::new("Peers/Seeds", 20, 11, 4),
+    ];
+
+    pub fn render(&mut self, focus: &Focus, theme: &Theme, width: u16) -> (Table<'_>, u16) {
+        let focused = matches!(focus, Focus::TorrentTable);
+
+        if let Connection::Disconnected {
+            error, attempts, ..
+        } = &self.connection
+        {
+            let row = Row::new([format!(
+                "{}  transmission-daemon unreachable ({attempts} attempt/s): {error}",
+                theme.icons.error
+            )]);
+
+            return (
+                Table::new([row], [Constraint::Percentage(100)])
+                    .block(
+                        theme
+                            .panel_block(focused)
+                            .title(" Downloads ")
+                            .title_style(theme.title_style())
+                            .title_alignment(ratatui::layout::Alignment::Center),
+                    )
+                    .style(Style::default().fg(theme.header)),
+                3,
+            );
+        }
 
-        let header = Row::new(["Name", "Size", "Downloaded", "Status", "Peers/Seeds"])
-            .style(Style::new().dark_gray().bold())
-            .bottom_margin(0);
+        let columns = Columns::fit(&Self::COLUMNS, width, theme.icons.highlight);
+        let header = columns.header(theme.header_style(), None, &theme.icons);
 
         let mut rows: Vec<Vec<String>> = Vec::new();
 
         for torrent in &self.torrents {
-            let status = if *torrent.is_stalled.as_ref().unwrap() {
-                String::from("  Stalled")
-            } else if *torrent.percent_done.as_ref().unwrap() == 1.0 {
-                String::from("󰸞  Finished")
-            } else {
-                String::from("  Downloading")
+            let status = match Self::torrent_status(torrent) {
+                "Stalled" => format!("{}  Stalled", theme.icons.stalled),
+                "Finished" => format!("{}  Finished", theme.icons.success),
+                status => format!("{}  {status}", theme.icons.downloading),
             };
 
             rows.push(vec![
@@ -175,8 +310,8 @@ impl Transmission {
         }
 
         let rows = rows
-            .iter()
-            .map(|item| Row::new(item.iter().cloned()))
+            .into_iter()
+            .map(|item| columns.row(item))
             .collect::<Vec<_>>();
 
         let constraint = if rows.len() < 6 {
@@ -185,42 +320,21 @@ impl Transmission {
             10
         };
 
-        let border_style = if matches!(focus, Focus::TorrentTable) {
-            Style::default()
-                .fg(Color::Gray)
-                .add_modifier(Modifier::BOLD)
-        } else {
-            Style::default().fg(Color::DarkGray)
-        };
-
         (
-            Table::new(rows, widths)
+            Table::new(rows, columns.widths())
                 .header(header)
                 .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .borders(Borders::ALL)
-                        .border_type(BorderType::Thick)
-                        .border_style(border_style)
+                    theme
+                        .panel_block(focused)
                         .title(" Downloads ")
-                        .title_style(Style::new().white().bold())
+                        .title_style(theme.title_style())
                         .title_alignment(ratatui::layout::Alignment::Center),
                 )
                 .column_spacing(1)
-                .style(Style::default().fg(Color::White))
-                .row_highlight_style(
-                    Style::default()
-                        .bg(Color::DarkGray)
-                        .fg(Color::Black)
-                        .add_modifier(Modifier::BOLD),
-                )
-                .column_highlight_style(Color::Gray)
-                .cell_highlight_style(
-                    Style::default()
-                        .bg(Color::DarkGray)
-                        .fg(Color::Black)
-                        .add_modifier(Modifier::BOLD),
-                )
+                .style(theme.text_style())
+                .row_highlight_style(theme.highlight_style())
+                .column_highlight_style(theme.border_focused)
+                .cell_highlight_style(theme.highlight_style())
                 .highlight_symbol(" "),
             constraint,
         )
@@ -0,0 +1,285 @@
+use std::cmp::Ordering;
+
+use ratatui::{layout::Constraint, style::Style, widgets::Row};
+
+use crate::config::Icons;
+
+pub struct Column {
+    pub name: &'static str,
+    pub percentage: u16,
+    pub min_width: u16,
+    /// Lower is more important. Columns with the highest priority are hidden first
+    pub priority: u8,
+}
+
+impl Column {
+    pub const fn new(name: &'static str, percentage: u16, min_width: u16, priority: u8) -> Self {
+        Self {
+            name,
+            percentage,
+            min_width,
+            priority,
+        }
+    }
+}
+
+/// Columns of a table which fit in a width
+pub struct Columns {
+    columns: &'static [Column],
+    visible: Vec<usize>,
+}
+
+impl Columns {
+    /// Width of the whole table, borders and highlight symbol included
+    pub fn fit(columns: &'static [Column], width: u16, highlight_symbol: &str) -> Columns {
+        let mut visible: Vec<usize> = (0..columns.len()).collect();
+        let width = width.saturating_sub(2 + highlight_symbol.chars().count() as u16);
+
+        // Tables use a column spacing of 1
+        let needed = |visible: &[usize]| {
+            visible.iter().map(|&i| columns[i].min_width).sum::<u16>()
+                + visible.len().saturating_sub(1) as u16
+        };
+
+        while visible.len() > 1 && needed(&visible) > width {
+            if let Some(position) = visible
+                .iter()
+                .enumerate()
+                .max_by_key(|&(_, &i)| columns[i].priority)
+                .map(|(position, _)| position)
+            {
+                visible.remove(position);
+            }
+        }
+
+        Self { columns, visible }
+    }
+
+    /// Percentages of the visible columns, scaled to fill the table
+    pub fn widths(&self) -> Vec<Constraint> {
+        let total = self
+            .visible
+            .iter()
+            .map(|&i| self.columns[i].percentage)
+            .sum::<u16>()
+            .max(1);
+
+        self.visible
+            .iter()
+            .map(|&i| Constraint::Percentage(self.columns[i].percentage * 100 / total))
+            .collect()
+    }
+
+    /// Names of the visible columns, the sorted one followed by its direction
+    pub fn header(&self, style: Style, sort: Option<&Sort>, icons: &Icons) -> Row<'static> {
+        Row::new(self.visible.iter().map(|&i| {
+            let name = self.columns[i].name;
+            match sort.and_then(|sort| sort.direction(i)) {
+                Some(false) => format!("{name} {}", icons.ascending),
+                Some(true) => format!("{name} {}", icons.descending),
+                None => name.to_string(),
+            }
+        }))
+        .style(style)
+        .bottom_margin(0)
+    }
+
+    pub fn row(&self, cells: Vec<String>) -> Row<'static> {
+        Row::new(
+            cells
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| self.visible.contains(i))
+                .map(|(_, cell)| cell),
+        )
+    }
+}
+
+/// Value of a cell to sort by
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum SortKey {
+    Number(f64),
+    Text(String),
+}
+
+impl SortKey {
+    pub fn text(text: &str) -> SortKey {
+        SortKey::Text(text.to_lowercase())
+    }
+}
+
+/// Bytes of a size like "1.4 GB" or "700 MiB", 0 when it is not a size
+pub fn parse_size(size: &str) -> f64 {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+
+    let number: f64 = number.replace(',', "").parse().unwrap_or_default();
+    let power = match unit.trim().chars().next().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => 1,
+        Some('M') => 2,
+        Some('G') => 3,
+        Some('T') => 4,
+        _ => 0,
+    };
+
+    number * 1024f64.powi(power)
+}
+
+/// Numbers of a text like "12 / 340", in order
+pub fn parse_numbers(text: &str) -> Vec<f64> {
+    text.split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .filter_map(|number| number.parse().ok())
+        .collect()
+}
+
+/// Client side order of the rows of a table. Rows keep the order of their source until
+/// a column is picked
+#[derive(Debug, Default)]
+pub struct Sort {
+    pub column: Option<usize>,
+    pub descending: bool,
+    /// Position in the source of each row, to go back to its order
+    ranks: Vec<usize>,
+}
+
+impl Sort {
+    /// Each sortable column ascending then descending, then the order of the source again
+    pub fn cycle(&mut self, sortable: &[usize]) {
+        match self
+            .column
+            .and_then(|column| sortable.iter().position(|&s| s == column))
+        {
+            Some(_) if !self.descending => self.descending = true,
+            Some(position) => {
+                self.column = sortable.get(position + 1).copied();
+                self.descending = false;
+            }
+            None => {
+                self.column = sortable.first().copied();
+                self.descending = false;
+            }
+        }
+    }
+
+    /// Sorts by the next column. Returns the row of the selected row after sorting
+    pub fn next<T>(
+        &mut self,
+        sortable: &[usize],
+        rows: &mut Vec<T>,
+        key: impl Fn(&T, usize) -> SortKey,
+        selected: Option<usize>,
+    ) -> Option<usize> {
+        let rank = selected.map(|row| self.rank(row));
+        self.cycle(sortable);
+        self.apply(rows, key);
+
+        rank.and_then(|rank| self.ranks.iter().position(|&r| r == rank))
+            .or(selected)
+    }
+
+    /// Position of the row in its source
+    pub fn rank(&self, row: usize) -> usize {
+        self.ranks.get(row).copied().unwrap_or(row)
+    }
+
+    /// Whether the column is sorted descending, None when it is not sorted
+    pub fn direction(&self, column: usize) -> Option<bool> {
+        (self.column == Some(column)).then_some(self.descending)
+    }
+
+    /// New rows of the source, sorted by the current column
+    pub fn reset<T>(&mut self, rows: &mut Vec<T>, key: impl Fn(&T, usize) -> SortKey) {
+        self.ranks = (0..rows.len()).collect();
+        self.apply(rows, key);
+    }
+
+    /// Sorts the rows by the key of the sorted column, ties keep the order of the source
+    pub fn apply<T>(&mut self, rows: &mut Vec<T>, key: impl Fn(&T, usize) -> SortKey) {
+        if self.ranks.len() != rows.len() {
+            self.ranks = (0..rows.len()).collect();
+        }
+
+        let mut ranked: Vec<(usize, T)> = self.ranks.drain(..).zip(rows.drain(..)).collect();
+        ranked.sort_by_key(|&(rank, _)| rank);
+
+        if let Some(column) = self.column {
+            ranked.sort_by(|(_, a), (_, b)| {
+                let ordering = key(a, column)
+                    .partial_cmp(&key(b, column))
+                    .unwrap_or(Ordering::Equal);
+                if self.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        }
+
+        (self.ranks, *rows) = ranked.into_iter().unzip();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::Constraint;
+
+    use crate::elements::{Column, Columns, Sort, SortKey, parse_numbers, parse_size};
+
+    const COLUMNS: [Column; 4] = [
+        Column::new("Year", 10, 4, 1),
+        Column::new("Name", 50, 20, 0),
+        Column::new("Genre", 30, 15, 3),
+        Column::new("Rating", 10, 6, 2),
+    ];
+
+    #[test]
+    fn columns_collapse_by_priority() {
+        let all = Columns::fit(&COLUMNS, 100, "");
+        assert_eq!(all.visible, vec![0, 1, 2, 3]);
+        assert_eq!(all.widths()[1], Constraint::Percentage(50));
+
+        let no_genre = Columns::fit(&COLUMNS, 40, "");
+        assert_eq!(no_genre.visible, vec![0, 1, 3]);
+        assert_eq!(no_genre.widths()[1], Constraint::Percentage(71));
+
+        let name_only = Columns::fit(&COLUMNS, 10, "");
+        assert_eq!(name_only.visible, vec![1]);
+        assert_eq!(name_only.widths(), vec![Constraint::Percentage(100)]);
+    }
+
+    #[test]
+    fn sort_cycles_columns_and_parses_numbers() {
+        assert_eq!(parse_size("1.4 GB"), 1.4 * 1024.0 * 1024.0 * 1024.0);
+        assert_eq!(parse_size("700 MB"), 700.0 * 1024.0 * 1024.0);
+        assert!(parse_size("1.4 GB") > parse_size("900.5 MB"));
+        assert_eq!(parse_size("unknown"), 0.0);
+        assert_eq!(parse_numbers("12 / 340"), vec![12.0, 340.0]);
+
+        let mut rows = vec!["900 MB", "1.4 GB", "90 MB"];
+        let key = |size: &&str, _| SortKey::Number(parse_size(size));
+        let mut sort = Sort::default();
+        sort.reset(&mut rows, key);
+
+        sort.cycle(&[1, 3]);
+        sort.apply(&mut rows, key);
+        assert_eq!((sort.column, sort.descending), (Some(1), false));
+        assert_eq!(rows, vec!["90 MB", "900 MB", "1.4 GB"]);
+
+        sort.cycle(&[1, 3]);
+        sort.apply(&mut rows, key);
+        assert_eq!(rows, vec!["1.4 GB", "900 MB", "90 MB"]);
+
+        sort.cycle(&[1, 3]);
+        assert_eq!((sort.column, sort.descending), (Some(3), false));
+
+        // After the last column the rows go back to the order of the source
+        sort.cycle(&[1, 3]);
+        sort.cycle(&[1, 3]);
+        sort.apply(&mut rows, key);
+        assert_eq!(sort.column, None);
+        assert_eq!(rows, vec!["900 MB", "1.4 GB", "90 MB"]);
+    }
+}
@@ -0,0 +1,97 @@
+use ratatui::{
+    layout::{Constraint, Rect},
+    widgets::{Row, Table, TableState},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::config::Theme;
+
+/// Dropdown of the filters or values completing the word before the cursor
+#[derive(Debug, Default)]
+pub struct Completion {
+    pub items: Vec<String>,
+    pub table_state: TableState,
+    /// Byte of the search text where the completed word starts
+    pub start: usize,
+}
+
+impl Completion {
+    const MAX_ROWS: u16 = 8;
+
+    pub fn is_open(&self) -> bool {
+        !self.items.is_empty()
+    }
+
+    pub fn open(&mut self, start: usize, items: Vec<String>) {
+        self.items = items;
+        self.start = start;
+        self.table_state.select(Some(0));
+    }
+
+    pub fn close(&mut self) {
+        self.items.clear();
+    }
+
+    pub fn next(&mut self) {
+        let selected = self.table_state.selected().map_or(0, |i| i + 1);
+        self.table_state
+            .select(Some(selected % self.items.len().max(1)));
+    }
+
+    pub fn previous(&mut self) {
+        let len = self.items.len().max(1);
+        let selected = self.table_state.selected().map_or(0, |i| i + len - 1);
+        self.table_state.select(Some(selected % len));
+    }
+
+    pub fn selected(&self) -> Option<&str> {
+        self.table_state
+            .selected()
+            .and_then(|i| self.items.get(i))
+            .map(String::as_str)
+    }
+
+    /// Under the completed word of the search box, inside the bounds
+    pub fn area(&self, bounds: Rect, x: u16, y: u16) -> Rect {
+        let width = self
+            .items
+            .iter()
+            .map(|item| item.width() as u16)
+            .max()
+            .unwrap_or_default()
+            + 4;
+        let height = (self.items.len() as u16).min(Self::MAX_ROWS) + 2;
+
+        let width = width.min(bounds.width);
+        let x = x.min(bounds.right().saturating_sub(width));
+
+        Rect::new(x, y, width, height).intersection(bounds)
+    }
+
+    pub fn render(&self, theme: &Theme) -> Table<'static> {
+        let rows = self.items.iter().map(|item| Row::new([item.clone()]));
+
+        Table::new(rows, [Constraint::Percentage(100)])
+            .block(theme.popup_block())
+            .style(theme.text_style())
+            .row_highlight_style(theme.highlight_style())
+            .highlight_symbol(theme.icons.popup_highlight)
+    }
+}
+
+/// Longest start shared by all the items
+pub fn common_prefix(items: &[String]) -> &str {
+    let Some(first) = items.first() else {
+        return "";
+    };
+
+    let len = items[1..].iter().fold(first.len(), |len, item| {
+        first[..len]
+            .char_indices()
+            .zip(item.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(len.min(item.len()), |((i, _), _)| i)
+    });
+
+    &first[..len]
+}
@@ -0,0 +1,437 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use image::RgbImage;
+
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Padding, Paragraph, Wrap},
+};
+use serde::Deserialize;
+use yts_movies::Movie;
+
+use crate::{
+    config::Theme,
+    elements::{Graphics, Poster, load_poster},
+};
+
+/// More about a movie, from the YTS API
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Details {
+    pub summary: String,
+    /// Minutes, 0 when unknown
+    pub runtime: u32,
+    pub mpa_rating: String,
+    pub language: String,
+    pub imdb_code: String,
+    pub cast: Vec<String>,
+    pub qualities: Vec<String>,
+    /// Link of the cover image
+    pub cover: String,
+    /// Cover decoded, None when posters are off or it could not be loaded
+    pub poster: Option<Arc<RgbImage>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ApiResponse<T> {
+    data: T,
+}
+
+#[derive(Deserialize, Debug)]
+struct ApiList {
+    #[serde(default)]
+    movies: Vec<ApiMovie>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ApiDetails {
+    movie: ApiMovie,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct ApiMovie {
+    id: u64,
+    url: String,
+    title: String,
+    year: u32,
+    imdb_code: String,
+    runtime: u32,
+    mpa_rating: String,
+    language: String,
+    description_full: String,
+    medium_cover_image: String,
+    cast: Vec<ApiCast>,
+    torrents: Vec<ApiTorrent>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct ApiCast {
+    name: String,
+    character_name: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct ApiTorrent {
+    quality: String,
+    #[serde(rename = "type")]
+    kind: String,
+    video_codec: String,
+}
+
+impl From<ApiMovie> for Details {
+    fn from(movie: ApiMovie) -> Self {
+        let cast = movie
+            .cast
+            .into_iter()
+            .map(|cast| match cast.character_name.as_str() {
+                "" => cast.name,
+                character => format!("{} as {character}", cast.name),
+            })
+            .collect();
+
+        let mut qualities: Vec<String> = vec![];
+        for torrent in movie.torrents {
+            let mut quality = format!("{} {}", torrent.quality, torrent.kind);
+            if torrent.video_codec.eq_ignore_ascii_case("x265") {
+                quality.push_str(" x265");
+            }
+            if !qualities.contains(&quality) {
+                qualities.push(quality);
+            }
+        }
+
+        Self {
+            summary: movie.description_full,
+            runtime: movie.runtime,
+            mpa_rating: movie.mpa_rating,
+            language: movie.language,
+            imdb_code: movie.imdb_code,
+            cast,
+            qualities,
+            cover: movie.medium_cover_image,
+            poster: None,
+        }
+    }
+}
+
+/// Toggleable pane with the details of the highlighted movie, fetched once per movie
+pub struct MovieDetails {
+    pub show: bool,
+    /// Details or the error of each fetched movie and when it was fetched, by movie link
+    cache: HashMap<String, (Result<Details, String>, Instant)>,
+    /// Cached links, the least recently highlighted first
+    order: VecDeque<String>,
+    fetching: HashSet<String>,
+    /// Highlighted movie and since when, to not fetch every row while scrolling
+    highlighted: Option<(String, Instant)>,
+    host: String,
+    poster_dir: PathBuf,
+    pub poster: Poster,
+}
+
+impl MovieDetails {
+    const DELAY: Duration = Duration::from_millis(250);
+    /// Movies kept with their poster, the least recently highlighted go first
+    const CACHED: usize = 50;
+    /// Failed fetches are tried again after this
+    const RETRY: Duration = Duration::from_secs(30);
+
+    pub fn new(host: &str, poster_dir: PathBuf, graphics: Graphics) -> MovieDetails {
+        Self {
+            show: false,
+            cache: HashMap::new(),
+            order: VecDeque::new(),
+            fetching: HashSet::new(),
+            highlighted: None,
+            host: host.trim_end_matches('/').to_string(),
+            poster_dir,
+            poster: Poster::new(graphics),
+        }
+    }
+
+    /// Whether the highlighted movie should be fetched now, which marks it as fetching
+    pub fn should_fetch(&mut self, link: &str, now: Instant) -> bool {
+        if !self.show || self.fetching.contains(link) {
+            return false;
+        }
+
+        match self.cache.get(link) {
+            Some((Err(_), at)) if now.duration_since(*at) >= Self::RETRY => self.forget(link),
+            Some(_) => {
+                self.touch(link);
+                return false;
+            }
+            None => {}
+        }
+
+        match &self.highlighted {
+            Some((highlighted, since)) if highlighted == link => {
+                if now.duration_since(*since) < Self::DELAY {
+                    return false;
+                }
+                self.fetching.insert(link.to_string());
+                true
+            }
+            _ => {
+                self.highlighted = Some((link.to_string(), now));
+                false
+            }
+        }
+    }
+
+    pub fn fetched(&mut self, link: String, details: anyhow::Result<Details>, now: Instant) {
+        self.fetching.remove(&link);
+        self.forget(&link);
+        self.order.push_back(link.clone());
+        self.cache
+            .insert(link, (details.map_err(|e| e.to_string()), now));
+
+        while self.order.len() > Self::CACHED {
+            if let Some(oldest) = self.order.pop_front() {
+                self.cache.remove(&oldest);
+            }
+        }
+    }
+
+    fn touch(&mut self, link: &str) {
+        if let Some(i) = self.order.iter().position(|cached| cached == link)
+            && let Some(link) = self.order.remove(i)
+        {
+            self.order.push_back(link);
+        }
+    }
+
+    fn forget(&mut self, link: &str) {
+        self.cache.remove(link);
+        self.order.retain(|cached| cached != link);
+    }
+
+    /// The id of the movie is found by its page link, then its details are asked with the cast.
+    /// The cover is loaded with them, a missing cover only leaves the pane without poster
+    pub fn fetch(
+        &self,
+        movie: &Movie,
+    ) -> impl Future<Output = anyhow::Result<Details>> + Send + 'static {
+        let api = format!("{}/api/v2", self.host);
+        let name = movie.name.clone();
+        let year = movie.year;
+        let link = movie.link.trim_end_matches('/').to_string();
+        let poster_dir = self.poster_dir.clone();
+        let posters = self.poster.graphics != Graphics::Off;
+
+        async move {
+            let client = reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()?;
+
+            let list: ApiResponse<ApiList> = client
+                .get(format!("{api}/list_movies.json"))
+                .query(&[("query_term", name.as_str()), ("limit", "50")])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            let id = list
+                .data
+                .movies
+                .iter()
+                .find(|m| m.url.trim_end_matches('/') == link)
+                .or_else(|| {
+                    list.data
+                        .movies
+                        .iter()
+                        .find(|m| m.title.eq_ignore_ascii_case(&name) && m.year == year)
+                })
+                .map(|m| m.id)
+                .ok_or_else(|| anyhow::anyhow!("{name} ({year}) not found in the YTS API"))?;
+
+            let details: ApiResponse<ApiDetails> = client
+                .get(format!("{api}/movie_details.json"))
+                .query(&[("movie_id", id.to_string().as_str()), ("with_cast", "true")])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            let mut details = Details::from(details.data.movie);
+            if posters && !details.cover.is_empty() {
+                details.poster =
+                    load_poster(&client, poster_dir, &details.imdb_code, &details.cover)
+                        .await
+                        .ok()
+                        .map(Arc::new);
+            }
+
+            Ok(details)
+        }
+    }
+
+    pub fn poster_image(&self, movie: &Movie) -> Option<Arc<RgbImage>> {
+        match self.cache.get(&movie.link) {
+            Some((Ok(details), _)) => details.poster.clone(),
+            _ => None,
+        }
+    }
+
+    /// Columns of the poster inside the pane, 0 when the text would be too narrow.
+    /// Covers are 2:3 and cells about 1:2
+    pub fn poster_width(&self, inner: Rect) -> u16 {
+        let width = inner.height * 4 / 3;
+        if self.poster.graphics == Graphics::Off || inner.width < width + 30 {
+            0
+        } else {
+            width
+        }
+    }
+
+    /// The text leaves room on the left for a poster of `poster_width` columns
+    pub fn render(
+        &self,
+        movie: Option<&Movie>,
+        theme: &Theme,
+        poster_width: u16,
+    ) -> Paragraph<'static> {
+        let padding = match poster_width {
+            0 => 0,
+            width => width + 1,
+        };
+        let block = theme
+            .panel_block(false)
+            .title(" Details ")
+            .title_style(theme.title_style())
+            .padding(Padding::left(padding));
+
+        let label = |name: &str, value: String| {
+            Line::from(vec![
+                Span::styled(format!("{name}: "), theme.header_style()),
+                Span::raw(value),
+            ])
+        };
+
+        let cached = |movie: &Movie| self.cache.get(&movie.link).map(|(details, _)| details);
+        let lines = match movie.map(|movie| (movie, cached(movie))) {
+            None => vec![Line::raw("No movie selected")],
+            Some((_, Some(Ok(details)))) => {
+                let unknown = |text: &str| match text {
+                    "" => String::from("-"),
+                    text => text.to_string(),
+                };
+
+                vec![
+                    label(
+                        "Runtime",
+                        match details.runtime {
+                            0 => String::from("-"),
+                            minutes => format!("{}h {:02}m", minutes / 60, minutes % 60),
+                        },
+                    ),
+                    label("MPA rating", unknown(&details.mpa_rating)),
+                    label("Language", unknown(&details.language)),
+                    label("IMDb", unknown(&details.imdb_code)),
+                    label("Qualities", unknown(&details.qualities.join(", "))),
+                    label("Cast", unknown(&details.cast.join(", "))),
+                    Line::raw(""),
+                    Line::raw(details.summary.clone()),
+                ]
+            }
+            Some((_, Some(Err(e)))) => vec![Line::styled(
+                format!("{}  Details not available: {e}", theme.icons.error),
+                Style::default().fg(theme.error),
+            )],
+            Some((movie, None)) => vec![Line::raw(format!(
+                "{}  Loading {}...",
+                theme.icons.downloading, movie.name
+            ))],
+        };
+
+        Paragraph::new(lines)
+            .style(theme.text_style())
+            .wrap(Wrap { trim: true })
+            .block(block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::PathBuf,
+        time::{Duration, Instant},
+    };
+
+    use crate::elements::{Details, Graphics, MovieDetails};
+
+    #[test]
+    fn fetch_once_after_the_highlight_stays() {
+        let mut details = MovieDetails::new("https://yts.lt/", PathBuf::new(), Graphics::Off);
+        let now = Instant::now();
+        let later = now + Duration::from_secs(1);
+
+        assert!(!details.should_fetch("matrix", later));
+        details.show = true;
+
+        assert!(!details.should_fetch("alien", now));
+        assert!(!details.should_fetch("matrix", now));
+        assert!(details.should_fetch("matrix", later));
+        assert!(!details.should_fetch("matrix", later));
+
+        details.fetched(String::from("matrix"), Ok(Details::default()), later);
+        assert!(!details.should_fetch("matrix", later + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn retry_errors_and_forget_the_oldest() {
+        let mut details = MovieDetails::new("https://yts.lt/", PathBuf::new(), Graphics::Off);
+        details.show = true;
+        let now = Instant::now();
+
+        details.fetched(String::from("matrix"), Err(anyhow::anyhow!("timeout")), now);
+        details.highlighted = Some((String::from("matrix"), now));
+        assert!(!details.should_fetch("matrix", now + Duration::from_secs(1)));
+        assert!(details.should_fetch("matrix", now + MovieDetails::RETRY));
+
+        for i in 0..MovieDetails::CACHED {
+            details.fetched(i.to_string(), Ok(Details::default()), now);
+        }
+        details.should_fetch("0", now);
+        details.fetched(String::from("matrix"), Ok(Details::default()), now);
+
+        assert_eq!(details.cache.len(), MovieDetails::CACHED);
+        assert!(details.cache.contains_key("0"));
+        assert!(!details.cache.contains_key("1"));
+    }
+
+    #[test]
+    fn details_from_the_api() {
+        let json = r#"{"status": "ok", "data": {"movie": {
+            "id": 10, "imdb_code": "tt0133093", "runtime": 136, "language": "en",
+            "mpa_rating": "R", "description_full": "A hacker...",
+            "medium_cover_image": "https://yts.lt/assets/images/movies/the_matrix/medium-cover.jpg",
+            "cast": [{"name": "Keanu Reeves", "character_name": "Neo"}, {"name": "Extra"}],
+            "torrents": [
+                {"quality": "1080p", "type": "bluray", "video_codec": "x264"},
+                {"quality": "1080p", "type": "bluray", "video_codec": "x265"},
+                {"quality": "1080p", "type": "bluray", "video_codec": "x264"}
+            ]
+        }}}"#;
+
+        let response: super::ApiResponse<super::ApiDetails> = serde_json::from_str(json).unwrap();
+        let details = Details::from(response.data.movie);
+
+        assert_eq!(details.runtime, 136);
+        assert!(details.cover.ends_with("medium-cover.jpg"));
+        assert_eq!(details.cast, vec!["Keanu Reeves as Neo", "Extra"]);
+        assert_eq!(details.qualities, vec!["1080p bluray", "1080p bluray x265"]);
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Debug, PartialEq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum Focus {
     #[default]
     InputBox,
@@ -7,4 +7,8 @@ pub enum Focus {
     PopupNotification,
     PopupTorrent,
     PopupSubtitle,
+    PopupGenre,
+    PopupPage,
+    Watchlist,
+    PopupNote,
 }
@@ -0,0 +1,104 @@
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Row, Table},
+};
+
+use crate::config::{KeyContext, KeyMap, Theme};
+use crate::elements::centered;
+
+pub struct PopupHelp {
+    pub show: bool,
+    pub context: KeyContext,
+}
+
+impl PopupHelp {
+    const KEYS_WIDTH: u16 = 20;
+
+    pub fn new() -> PopupHelp {
+        Self {
+            show: false,
+            context: KeyContext::Search,
+        }
+    }
+
+    pub fn open(&mut self, context: KeyContext) {
+        self.context = context;
+        self.show = true;
+    }
+
+    fn rows(&self, keymap: &KeyMap) -> Vec<[String; 2]> {
+        keymap
+            .bindings(self.context)
+            .iter()
+            .filter(|(_, chords)| !chords.is_empty())
+            .map(|(action, chords)| {
+                let keys = chords
+                    .iter()
+                    .map(|chord| chord.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ");
+
+                [keys, action.description(self.context).to_string()]
+            })
+            .collect()
+    }
+
+    pub fn area(&self, area: Rect, keymap: &KeyMap) -> Rect {
+        let rows = self.rows(keymap);
+        let width = rows
+            .iter()
+            .map(|[_, description]| description.chars().count() as u16)
+            .max()
+            .unwrap_or_default()
+            + Self::KEYS_WIDTH
+            + 5;
+
+        centered(area, width, rows.len() as u16 + 3)
+    }
+
+    pub fn render(&self, keymap: &KeyMap, theme: &Theme) -> Table<'static> {
+        let rows = self.rows(keymap).into_iter().map(|[keys, description]| {
+            Row::new([
+                Span::styled(keys, theme.title_style()),
+                Span::raw(description),
+            ])
+        });
+
+        let header = Row::new(["Keys", "Action"]).style(theme.header_style());
+
+        Table::new(
+            rows,
+            [Constraint::Length(Self::KEYS_WIDTH), Constraint::Fill(1)],
+        )
+        .header(header)
+        .block(
+            theme
+                .popup_block()
+                .title(format!(" Help: {} ", self.context.name()))
+                .title_style(theme.title_style()),
+        )
+        .column_spacing(1)
+        .style(theme.text_style())
+    }
+}
+
+/// One line with the first key of the main actions of a context
+pub fn hint_bar(keymap: &KeyMap, context: KeyContext, theme: &Theme) -> Line<'static> {
+    let mut spans = vec![];
+
+    for (action, chords) in keymap.bindings(context) {
+        if let Some(chord) = chords.first()
+            && action.is_hint()
+        {
+            spans.push(Span::styled(format!(" {chord} "), theme.highlight_style()));
+            spans.push(Span::styled(
+                format!(" {}  ", action.name().replace('_', " ")),
+                Style::default().fg(theme.header),
+            ));
+        }
+    }
+
+    Line::from(spans)
+}
@@ -0,0 +1,263 @@
+use std::{fs, io, path::PathBuf};
+
+/// Submitted searches, oldest first, with the state of Up/Down recall and Ctrl-R search
+#[derive(Debug)]
+pub struct History {
+    entries: Vec<String>,
+    size: usize,
+    path: PathBuf,
+    /// Entry shown by Up/Down, None while editing a new query
+    position: Option<usize>,
+    /// Text of the search box before browsing the history
+    draft: String,
+    search: Option<ReverseSearch>,
+}
+
+#[derive(Debug)]
+struct ReverseSearch {
+    pattern: String,
+    matched: Option<usize>,
+    failed: bool,
+}
+
+impl History {
+    /// A size of 0 disables the history
+    pub fn new(path: PathBuf, size: usize) -> History {
+        Self {
+            entries: vec![],
+            size,
+            path,
+            position: None,
+            draft: String::new(),
+            search: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.size > 0
+    }
+
+    pub fn load(&mut self) -> io::Result<()> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        match fs::read_to_string(&self.path) {
+            Ok(text) => {
+                self.entries = text
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(String::from)
+                    .collect();
+                self.truncate();
+                Ok(())
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self) -> impl Future<Output = anyhow::Result<()>> + Send + 'static {
+        let path = self.path.clone();
+        let mut text = self.entries.join("\n");
+        text.push('\n');
+
+        async move {
+            tokio::task::spawn_blocking(move || {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                fs::write(&path, text)?;
+                Ok(())
+            })
+            .await?
+        }
+    }
+
+    /// Adds a submitted query as the newest entry. Returns whether the history changed
+    pub fn add(&mut self, query: &str) -> bool {
+        self.position = None;
+
+        let query = query.trim();
+        if !self.is_enabled() || query.is_empty() {
+            return false;
+        }
+
+        self.entries.retain(|entry| entry != query);
+        self.entries.push(query.to_string());
+        self.truncate();
+        true
+    }
+
+    fn truncate(&mut self) {
+        if self.entries.len() > self.size {
+            self.entries.drain(..self.entries.len() - self.size);
+        }
+    }
+
+    /// Older entry for Up
+    pub fn previous(&mut self, current: &str) -> Option<String> {
+        let position = match self.position {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len() - 1
+            }
+            Some(position) => position.checked_sub(1)?,
+        };
+
+        self.position = Some(position);
+        Some(self.entries[position].clone())
+    }
+
+    /// Newer entry for Down, or back to the draft after the newest one
+    pub fn next(&mut self) -> Option<String> {
+        let position = self.position? + 1;
+
+        if position < self.entries.len() {
+            self.position = Some(position);
+            Some(self.entries[position].clone())
+        } else {
+            self.position = None;
+            Some(std::mem::take(&mut self.draft))
+        }
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.search.is_some()
+    }
+
+    pub fn start_search(&mut self, current: &str) {
+        if self.position.is_none() {
+            self.draft = current.to_string();
+        }
+
+        self.search = Some(ReverseSearch {
+            pattern: String::new(),
+            matched: None,
+            failed: false,
+        });
+    }
+
+    /// Newest entry matching the pattern, from `before` (exclusive) backwards
+    fn find(&mut self, before: usize) -> Option<String> {
+        let search = self.search.as_mut()?;
+        let pattern = search.pattern.to_lowercase();
+
+        match self.entries[..before]
+            .iter()
+            .rposition(|entry| entry.to_lowercase().contains(&pattern))
+        {
+            Some(index) => {
+                search.matched = Some(index);
+                search.failed = false;
+                Some(self.entries[index].clone())
+            }
+            None => {
+                search.failed = true;
+                None
+            }
+        }
+    }
+
+    pub fn search_push(&mut self, c: char) -> Option<String> {
+        let search = self.search.as_mut()?;
+        search.pattern.push(c);
+
+        // The current match is kept while it still matches
+        let before = search
+            .matched
+            .map_or(self.entries.len(), |matched| matched + 1);
+        self.find(before)
+    }
+
+    pub fn search_pop(&mut self) -> Option<String> {
+        let search = self.search.as_mut()?;
+        search.pattern.pop();
+        search.matched = None;
+        self.find(self.entries.len())
+    }
+
+    /// Older match of the same pattern, for Ctrl-R while searching
+    pub fn search_next(&mut self) -> Option<String> {
+        let search = self.search.as_ref()?;
+        let before = search.matched.unwrap_or(self.entries.len());
+        self.find(before)
+    }
+
+    /// Keeps the found entry in the search box
+    pub fn accept_search(&mut self) {
+        self.search = None;
+        self.position = None;
+    }
+
+    /// Gives back the text of the search box before searching
+    pub fn cancel_search(&mut self) -> String {
+        self.search = None;
+        self.position = None;
+        std::mem::take(&mut self.draft)
+    }
+
+    /// Title of the search box while searching
+    pub fn prompt(&self) -> Option<String> {
+        self.search.as_ref().map(|search| {
+            let failed = if search.failed { "failed " } else { "" };
+            format!(" ({failed}reverse-i-search)`{}' ", search.pattern)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::elements::History;
+
+    fn history(entries: &[&str]) -> History {
+        let mut history = History::new(PathBuf::new(), 3);
+        for entry in entries {
+            history.add(entry);
+        }
+        history
+    }
+
+    #[test]
+    fn recall_and_cap() {
+        let mut history = history(&["alien", "matrix", "heat year:1995", "matrix"]);
+        assert_eq!(history.entries, vec!["alien", "heat year:1995", "matrix"]);
+
+        assert_eq!(history.previous("ma").as_deref(), Some("matrix"));
+        assert_eq!(history.previous("").as_deref(), Some("heat year:1995"));
+        assert_eq!(history.previous("").as_deref(), Some("alien"));
+        assert_eq!(history.previous(""), None);
+        assert_eq!(history.next().as_deref(), Some("heat year:1995"));
+        assert_eq!(history.next().as_deref(), Some("matrix"));
+        assert_eq!(history.next().as_deref(), Some("ma"));
+        assert_eq!(history.next(), None);
+
+        let mut disabled = History::new(PathBuf::new(), 0);
+        assert!(!disabled.add("alien"));
+        assert_eq!(disabled.previous(""), None);
+    }
+
+    #[test]
+    fn reverse_search() {
+        let mut history = history(&["matrix reloaded", "heat", "matrix year:1999"]);
+        history.start_search("draft");
+
+        assert_eq!(
+            history.search_push('m').as_deref(),
+            Some("matrix year:1999")
+        );
+        assert_eq!(
+            history.search_push('a').as_deref(),
+            Some("matrix year:1999")
+        );
+        assert_eq!(history.search_next().as_deref(), Some("matrix reloaded"));
+        assert_eq!(history.search_next(), None);
+        assert!(history.prompt().unwrap().contains("failed"));
+
+        assert_eq!(history.cancel_search(), "draft");
+        assert!(!history.is_searching());
+    }
+}
@@ -1,33 +1,283 @@
 use ratatui::{
-    style::{Color, Modifier, Style},
-    widgets::{Block, BorderType, Borders, Paragraph},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
 };
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
-use crate::elements::Focus;
+use crate::config::Theme;
+use crate::elements::{Focus, QueryError};
 
 #[derive(Debug, Default)]
 pub struct InputBox {
-    pub text: String,
+    /// Title replacing the default one, like the reverse search prompt
+    pub prompt: Option<String>,
+    text: String,
+    /// Byte index of the cursor, always at a grapheme boundary
+    cursor: usize,
+    /// First visible column when the text is wider than the box
+    offset: u16,
+    /// Invalid tokens of the last search, until the text changes
+    errors: Vec<QueryError>,
 }
 
 impl InputBox {
-    pub fn render(&self, focus: &Focus) -> Paragraph<'_> {
-        let style = if matches!(focus, Focus::InputBox) {
-            Style::default()
-                .fg(Color::Gray)
-                .add_modifier(Modifier::BOLD)
-        } else {
-            Style::default().fg(Color::DarkGray)
-        };
-
-        let block = Block::default()
-            .borders(Borders::ALL)
-            .border_type(BorderType::Thick)
-            .border_style(style)
-            .title(" Search movie ");
-
-        Paragraph::new(self.text.clone())
-            .style(Style::default().fg(Color::Gray))
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, text: String) {
+        self.errors.clear();
+        self.text = text;
+        self.cursor = self.text.len();
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.errors.clear();
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    /// Pasted text in one line, without control characters
+    pub fn insert_str(&mut self, text: &str) {
+        let text = text
+            .replace("\r\n", "\n")
+            .replace(['\n', '\t'], " ")
+            .replace(|c: char| c.is_control(), "");
+
+        self.errors.clear();
+
+        self.text.insert_str(self.cursor, &text);
+        self.cursor += text.len();
+    }
+
+    pub fn delete_char(&mut self) {
+        self.errors.clear();
+        let start = self.previous_boundary();
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    pub fn delete_next_char(&mut self) {
+        self.errors.clear();
+        let end = self.next_boundary();
+        self.text.replace_range(self.cursor..end, "");
+    }
+
+    /// Deletes the word before the cursor and the spaces after it
+    pub fn delete_word(&mut self) {
+        self.errors.clear();
+        let start = self.text[..self.cursor]
+            .trim_end()
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(i, c)| i + c.len_utf8());
+
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    pub fn delete_to_start(&mut self) {
+        self.errors.clear();
+        self.text.replace_range(..self.cursor, "");
+        self.cursor = 0;
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.previous_boundary();
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = self.next_boundary();
+    }
+
+    pub fn move_start(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.text.len();
+    }
+
+    /// Word from the last space to the cursor, with the byte where it starts
+    pub fn word_before_cursor(&self) -> (usize, &str) {
+        let start = self.text[..self.cursor]
+            .rfind(char::is_whitespace)
+            .map_or(0, |i| i + 1);
+
+        (start, &self.text[start..self.cursor])
+    }
+
+    /// Replaces the text from start to the cursor with a completion.
+    /// Completed values are followed by a space, filters (`year:`) wait for their value
+    pub fn complete(&mut self, start: usize, word: &str) {
+        self.errors.clear();
+        self.text.replace_range(start..self.cursor, word);
+        self.cursor = start + word.len();
+
+        if !word.ends_with(':') {
+            match self.text[self.cursor..].chars().next() {
+                Some(c) if c.is_whitespace() => self.cursor += c.len_utf8(),
+                _ => {
+                    self.text.insert(self.cursor, ' ');
+                    self.cursor += 1;
+                }
+            }
+        }
+    }
+
+    pub fn set_errors(&mut self, errors: Vec<QueryError>) {
+        self.errors = errors;
+    }
+
+    fn previous_boundary(&self) -> usize {
+        self.text[..self.cursor]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor..]
+            .graphemes(true)
+            .next()
+            .map_or(self.cursor, |grapheme| self.cursor + grapheme.len())
+    }
+
+    /// Column of the cursor inside the box, after the horizontal scroll
+    pub fn cursor_column(&self) -> u16 {
+        self.column(self.cursor)
+    }
+
+    /// Column of a byte of the text inside the box, after the horizontal scroll
+    pub fn column(&self, index: usize) -> u16 {
+        (self.text[..index].width() as u16).saturating_sub(self.offset)
+    }
+
+    fn cursor_width(&self) -> u16 {
+        self.text[..self.cursor].width() as u16
+    }
+
+    /// Text with the invalid tokens marked
+    fn highlight_errors(&self, theme: &Theme) -> Line<'static> {
+        let invalid = Style::default()
+            .fg(theme.error)
+            .add_modifier(Modifier::UNDERLINED | Modifier::BOLD);
+
+        let mut spans = vec![];
+        let mut start = 0;
+
+        for error in &self.errors {
+            let span = error.span.clone();
+            if span.start < start || span.end > self.text.len() {
+                continue;
+            }
+            spans.push(Span::raw(self.text[start..span.start].to_string()));
+            spans.push(Span::styled(self.text[span.clone()].to_string(), invalid));
+            start = span.end;
+        }
+        spans.push(Span::raw(self.text[start..].to_string()));
+
+        Line::from(spans)
+    }
+
+    pub fn render(&mut self, focus: &Focus, theme: &Theme, width: u16) -> Paragraph<'_> {
+        // Keep the cursor visible, the last column is left for it
+        let inner_width = width.saturating_sub(2).max(1);
+        let cursor = self.cursor_width();
+        if cursor < self.offset {
+            self.offset = cursor;
+        } else if cursor >= self.offset + inner_width {
+            self.offset = cursor - inner_width + 1;
+        }
+
+        let mut block = theme.panel_block(matches!(focus, Focus::InputBox)).title(
+            self.prompt
+                .as_deref()
+                .unwrap_or(" Search movie ")
+                .to_string(),
+        );
+
+        if let Some(error) = self.errors.first() {
+            let more = match self.errors.len() {
+                1 => String::new(),
+                n => format!(" (+{} more)", n - 1),
+            };
+            block = block.title_bottom(
+                Line::from(format!(" {}  {error}{more} ", theme.icons.error))
+                    .style(Style::default().fg(theme.error)),
+            );
+        }
+
+        Paragraph::new(self.highlight_errors(theme))
+            .style(Style::default().fg(theme.input))
+            .scroll((0, self.offset))
             .block(block)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::elements::InputBox;
+
+    #[test]
+    fn edit_by_graphemes() {
+        let mut input = InputBox::default();
+        input.insert_str("Ame\u{301}lie 千と千尋");
+        assert_eq!(input.cursor_column(), 15);
+
+        input.move_left();
+        input.delete_char();
+        assert_eq!(input.text(), "Ame\u{301}lie 千と尋");
+        assert_eq!(input.cursor_column(), 11);
+
+        input.move_start();
+        input.move_right();
+        input.move_right();
+        input.move_right();
+        assert_eq!(input.cursor_column(), 3);
+        input.delete_char();
+        assert_eq!(input.text(), "Amlie 千と尋");
+
+        input.move_end();
+        input.delete_word();
+        assert_eq!(input.text(), "Amlie ");
+        input.delete_word();
+        assert!(input.text().is_empty());
+    }
+
+    #[test]
+    fn paste_in_one_line() {
+        let mut input = InputBox::default();
+        input.insert_str("matrix");
+        input.move_start();
+        input.insert_str("the\n");
+        assert_eq!(input.text(), "the matrix");
+
+        input.delete_to_start();
+        assert_eq!(input.text(), "matrix");
+        input.delete_next_char();
+        assert_eq!(input.text(), "atrix");
+    }
+
+    #[test]
+    fn complete_the_word_before_the_cursor() {
+        let mut input = InputBox::default();
+        input.insert_str("alien or");
+        assert_eq!(input.word_before_cursor(), (6, "or"));
+
+        input.complete(6, "order:");
+        assert_eq!(input.word_before_cursor(), (6, "order:"));
+        input.complete(6, "order:latest");
+        assert_eq!(input.text(), "alien order:latest ");
+        assert_eq!(input.word_before_cursor(), (19, ""));
+
+        input.move_start();
+        input.insert_str("ra");
+        input.complete(0, "rating:7");
+        assert_eq!(input.text(), "rating:7 alien order:latest ");
+        assert_eq!(input.cursor_column(), 9);
+    }
+}
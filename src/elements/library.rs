@@ -0,0 +1,186 @@
+use std::{fs, path::Path};
+
+use yts_movies::Movie;
+
+/// Movies already downloading or on disk, to mark them in the movies table.
+/// Names are kept as their words, split once when they arrive instead of on each render
+#[derive(Debug, Default)]
+pub struct Library {
+    dir: String,
+    /// Words of the name and fraction done of the Transmission torrents
+    downloads: Vec<(Vec<String>, f32)>,
+    /// Words of the files and folders of the download dir, without extension
+    files: Vec<Vec<String>>,
+    /// Words of the subtitles of the download dir and its folders, without extension
+    subtitles: Vec<Vec<String>>,
+}
+
+/// Lowercase words of a name, split by anything which is not a letter or a digit
+fn words(name: &str) -> Vec<String> {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Whether a torrent or file name is of the movie, like "The Matrix (1999) [1080p] [YTS.MX]".
+/// The title goes first, followed by the year or nothing, so sequels do not match
+fn is_of(title: &[String], year: &str, name: &[String]) -> bool {
+    !title.is_empty()
+        && name.starts_with(title)
+        && name.get(title.len()).is_none_or(|next| next == year)
+}
+
+impl Library {
+    pub fn new(dir: &str) -> Library {
+        Self {
+            dir: dir.to_string(),
+            ..Library::default()
+        }
+    }
+
+    /// Name and fraction done of the Transmission torrents
+    pub fn set_downloads(&mut self, downloads: Vec<(String, f32)>) {
+        self.downloads = downloads
+            .into_iter()
+            .map(|(name, done)| (words(&name), done))
+            .collect();
+    }
+
+    /// Files and subtitles found by the scan
+    pub fn set_files(&mut self, files: Vec<String>, subtitles: Vec<String>) {
+        self.files = files.iter().map(|name| words(name)).collect();
+        self.subtitles = subtitles.iter().map(|name| words(name)).collect();
+    }
+
+    /// Files, folders and subtitles of the download dir. The subtitles of the folders are
+    /// included, torrents of YTS keep the movie in a folder. The dir is walked off the
+    /// async runtime
+    pub fn scan(&self) -> impl Future<Output = (Vec<String>, Vec<String>)> + Send + 'static {
+        let dir = self.dir.clone();
+
+        async move {
+            tokio::task::spawn_blocking(move || Self::walk(&dir))
+                .await
+                .unwrap_or_default()
+        }
+    }
+
+    fn walk(dir: &str) -> (Vec<String>, Vec<String>) {
+        let mut files = vec![];
+        let mut subtitles = vec![];
+
+        let entries = |path: &Path| {
+            fs::read_dir(path)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|entry| entry.path())
+        };
+
+        for path in entries(Path::new(&dir)) {
+            let inner = if path.is_dir() {
+                entries(&path).collect()
+            } else {
+                vec![]
+            };
+
+            for file in std::iter::once(path).chain(inner) {
+                let is_subtitle = file
+                    .extension()
+                    .is_some_and(|extension| extension.eq_ignore_ascii_case("srt"));
+                // Folders keep their whole name, dots included
+                let name = if file.is_dir() {
+                    file.file_name()
+                } else {
+                    file.file_stem()
+                };
+
+                if let Some(name) = name.and_then(|name| name.to_str()) {
+                    if is_subtitle {
+                        subtitles.push(name.to_string());
+                    } else if file.parent() == Some(Path::new(&dir)) {
+                        files.push(name.to_string());
+                    }
+                }
+            }
+        }
+
+        (files, subtitles)
+    }
+
+    /// "downloading 43%", "downloaded" and "has subtitles", empty for unknown movies
+    pub fn status(&self, movie: &Movie) -> String {
+        let title = words(&movie.name);
+        let year = movie.year.to_string();
+        let is_of = |name: &[String]| is_of(&title, &year, name);
+
+        let download = self
+            .downloads
+            .iter()
+            .find(|(name, _)| is_of(name))
+            .map(|(_, done)| *done);
+        let on_disk = self.files.iter().any(|name| is_of(name));
+        let subtitles = self.subtitles.iter().any(|name| is_of(name));
+
+        let mut status = match download {
+            Some(done) if done < 1.0 => format!("downloading {:.0}%", done * 100.0),
+            Some(_) => String::from("downloaded"),
+            None if on_disk => String::from("downloaded"),
+            None => String::new(),
+        };
+
+        if subtitles {
+            if status.is_empty() {
+                status.push_str("has subtitles");
+            } else {
+                status.push_str(", subtitles");
+            }
+        }
+
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yts_movies::Movie;
+
+    use crate::elements::Library;
+
+    #[test]
+    fn movies_match_downloads_and_files() {
+        let movie = |name: &str, year| Movie {
+            name: name.to_string(),
+            year,
+            rating: 8.7,
+            genres: vec![],
+            link: name.to_string(),
+        };
+        let matrix = movie("The Matrix", 1999);
+
+        let is_of = |name: &str| {
+            let mut library = Library::new("");
+            library.set_files(vec![name.to_string()], vec![]);
+            library.status(&matrix) == "downloaded"
+        };
+
+        assert!(is_of("The Matrix (1999) [1080p] [YTS.MX]"));
+        assert!(is_of("The.Matrix.1999.720p.BrRip.x264-YTS"));
+        assert!(is_of("the matrix"));
+        assert!(!is_of("The Matrix Reloaded (2003) [1080p] [YTS.MX]"));
+        assert!(!is_of("The Matrix (2021)"));
+
+        let mut library = Library::new("");
+        library.set_downloads(vec![(String::from("Alien (1979) [720p] [YTS.MX]"), 0.432)]);
+        library.set_files(
+            vec![String::from("The Matrix (1999) [1080p] [YTS.MX]")],
+            vec![String::from("The Matrix (1999)"), String::from("Heat")],
+        );
+
+        assert_eq!(library.status(&movie("Alien", 1979)), "downloading 43%");
+        assert_eq!(library.status(&matrix), "downloaded, subtitles");
+        assert_eq!(library.status(&movie("Heat", 1995)), "has subtitles");
+        assert_eq!(library.status(&movie("Ran", 1985)), "");
+    }
+}
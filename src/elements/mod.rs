@@ -1,9 +1,29 @@
+mod columns;
+mod completion;
+mod details;
 mod focus;
+mod help;
+mod history;
 mod input;
+mod library;
 mod movie_table;
+mod page_cache;
 mod popup;
+mod poster;
+mod query;
+mod watchlist;
 
+pub use columns::*;
+pub use completion::*;
+pub use details::*;
 pub use focus::*;
+pub use help::*;
+pub use history::*;
 pub use input::*;
+pub use library::*;
 pub use movie_table::*;
+pub use page_cache::*;
 pub use popup::*;
+pub use poster::*;
+pub use query::*;
+pub use watchlist::*;
@@ -1,36 +1,55 @@
-use std::time::Duration;
+use std::{ops::Range, sync::Arc, time::Duration};
 
 use ratatui::{
-    layout::Constraint,
-    style::{Color, Modifier, Style},
-    widgets::{Block, BorderType, Borders, Row, Table, TableState},
+    layout::Rect,
+    widgets::{Row, Table, TableState},
 };
-use yts_movies::{Filters, Page, Response, Yts};
+use tokio::{sync::Semaphore, task::JoinSet};
+use unicode_width::UnicodeWidthStr;
+use yts_movies::{Filters, Movie, Page, Response, Yts};
 
-use crate::elements::Focus;
+use crate::config::{Icons, Theme};
+use crate::elements::{Column, Columns, Focus, Library, PageCache, Query, Sort, SortKey};
 
 #[derive(Debug)]
-pub struct MovieTable<'a> {
+pub struct MovieTable {
     pub table_state: TableState,
     pub response: Response,
+    /// Query of the last search, used for the other pages
+    pub query: Query,
+    /// Order of the movies of the page, over the YTS order
+    pub sort: Sort,
     default_order: yts_movies::OrderBy,
-    yts: Yts<'a>,
+    host: String,
+    cache: PageCache,
 }
 
-impl<'a> MovieTable<'a> {
+impl MovieTable {
     const TITLE: &'static str = " YTS MOVIES ";
 
-    pub fn new(host: &'a str, default_order: yts_movies::OrderBy) -> Self {
+    const COLUMNS: [Column; 5] = [
+        Column::new("Year", 8, 4, 1),
+        Column::new("Name", 42, 20, 0),
+        Column::new("Genre", 22, 15, 3),
+        Column::new("Rating", 8, 6, 2),
+        Column::new("Status", 20, 12, 4),
+    ];
+
+    /// Year, name and rating
+    const SORTABLE: [usize; 3] = [0, 1, 3];
+
+    pub fn new(host: &str, default_order: yts_movies::OrderBy, cache: PageCache) -> Self {
         let mut table_state = TableState::default();
         table_state.select_first();
         table_state.select_first_column();
 
-        let yts = Yts::new(host, Duration::from_secs(30));
-
         Self {
             table_state,
-            yts,
+            host: host.to_string(),
             default_order,
+            cache,
+            query: Query::default(),
+            sort: Sort::default(),
             response: Response {
                 page: Page {
                     current: 0,
@@ -42,9 +61,54 @@ impl<'a> MovieTable<'a> {
         }
     }
 
+    /// Shows the movies of a response in the current order
+    /// The selection is kept, within the movies of the new page
+    pub fn set_response(&mut self, mut response: Response) {
+        self.sort.reset(&mut response.movies, Self::sort_key);
+        self.response = response;
+
+        let last = self.response.movies.len().saturating_sub(1);
+        let selected = self.table_state.selected().unwrap_or_default().min(last);
+        self.table_state.select(Some(selected));
+        *self.table_state.offset_mut() = 0;
+    }
+
+    /// Sorts the page by the next column, the selected movie stays selected
+    pub fn sort_next(&mut self) {
+        let selected = self.sort.next(
+            &Self::SORTABLE,
+            &mut self.response.movies,
+            Self::sort_key,
+            self.table_state.selected(),
+        );
+        self.table_state.select(selected);
+    }
+
+    fn sort_key(movie: &Movie, column: usize) -> SortKey {
+        match column {
+            0 => SortKey::Number(movie.year as f64),
+            3 => SortKey::Number(movie.rating as f64),
+            _ => SortKey::text(&movie.name),
+        }
+    }
+
+    /// YTS pages fetched at most to find movies matching the client side filters
+    const MAX_SCANNED_PAGES: u32 = 10;
+
+    /// Torrent lookups of the quality filter running at the same time
+    const MAX_TORRENT_LOOKUPS: usize = 8;
+
     pub fn footer(&self) -> String {
         let page = &self.response.page;
-        if page.total != 0 {
+        if page.total != 0 && self.query.filters_locally() {
+            // The total of YTS counts movies which are filtered out, only the page is known
+            format!(
+                " {} match on page {}/{} ",
+                self.response.movies.len(),
+                page.current,
+                page.of
+            )
+        } else if page.total != 0 {
             format!(
                 " {} Movie/s - Page {}/{} ",
                 page.total, page.current, page.of
@@ -54,133 +118,238 @@ impl<'a> MovieTable<'a> {
         }
     }
 
-    pub async fn search(&mut self, text: &str) -> yts_movies::Result {
-        let response = self
-            .yts
-            .search_with_filter(
-                self.clean_search_text(text),
-                Filters::default()
-                    .year(self.year_filter(text))
-                    .rating(self.rating_filter(text))
-                    .order_by(self.order_filter(text))
-                    .build(),
-            )
-            .await?;
-
-        self.response = response;
+    /// Footer and, from its start, the columns of the page controls with their pages
+    fn footer_controls(&self, icons: &Icons) -> (String, Vec<(Range<u16>, u32)>) {
+        let page = &self.response.page;
+        if page.of <= 1 {
+            return (self.footer(), vec![]);
+        }
 
-        Ok(())
-    }
+        let [first, previous, next, last] = icons.pages;
+        let pages = [
+            (first, 1),
+            (previous, page.current.saturating_sub(1).max(1)),
+            (next, (page.current + 1).min(page.of)),
+            (last, page.of),
+        ];
 
-    pub async fn next_page(&mut self, text: &str) -> yts_movies::Result {
-        let response = &self.response;
-        let next_page = response.page.current + 1;
-        if next_page <= response.page.of {
-            self.response = self
-                .yts
-                .search_with_filter(
-                    self.clean_search_text(text),
-                    Filters::default()
-                        .year(self.year_filter(text))
-                        .rating(self.rating_filter(text))
-                        .order_by(self.order_filter(text))
-                        .page(next_page)
-                        .build(),
-                )
-                .await?;
+        let mut text = String::from(" ");
+        let mut controls = vec![];
+        for (i, (icon, page)) in pages.into_iter().enumerate() {
+            match i {
+                1 => text.push(' '),
+                2 => text.push_str(&self.footer()),
+                3 => text.push(' '),
+                _ => {}
+            }
+            let start = text.width() as u16;
+            text.push_str(icon);
+            controls.push((start..text.width() as u16, page));
         }
+        text.push(' ');
 
-        Ok(())
+        (text, controls)
     }
 
-    pub async fn previous_page(&mut self, text: &str) -> yts_movies::Result {
-        let response = &self.response;
-        let prev_page = response.page.current - 1;
-        if prev_page > 0 {
-            self.response = self
-                .yts
-                .search_with_filter(
-                    self.clean_search_text(text),
-                    Filters::default()
-                        .year(self.year_filter(text))
-                        .rating(self.rating_filter(text))
-                        .order_by(self.order_filter(text))
-                        .page(prev_page)
-                        .build(),
-                )
-                .await?;
+    /// Page of the footer control under a column of the table area. Footers are centered
+    /// between the borders, like the block does
+    pub fn page_at(&self, area: Rect, icons: &Icons, x: u16) -> Option<u32> {
+        let (footer, controls) = self.footer_controls(icons);
+        let inner = area.width.saturating_sub(2);
+        let width = footer.width() as u16;
+        if width > inner {
+            return None;
         }
 
-        Ok(())
+        let column = x.checked_sub(area.x + 1 + (inner - width) / 2)?;
+        controls
+            .into_iter()
+            .find(|(columns, _)| columns.contains(&column))
+            .map(|(_, page)| page)
     }
 
-    fn clean_search_text(&self, text: &'a str) -> &'a str {
-        let indices: Vec<Option<usize>> = vec![
-            text.find(" year:"),
-            text.find(" rating:"),
-            text.find(" order:"),
-        ];
+    pub fn search(&self) -> impl Future<Output = yts_movies::Result<Response>> + Send + 'static {
+        self.fetch(None, false, false)
+    }
 
-        let min_index = indices.iter().filter_map(|&x| x).min();
+    pub fn search_page(
+        &self,
+        page: u32,
+    ) -> impl Future<Output = yts_movies::Result<Response>> + Send + 'static {
+        self.fetch(Some(page), false, false)
+    }
 
-        match min_index {
-            Some(index) => &text[..index],
-            None => text,
-        }
+    /// The current page again from YTS, replacing the cached one
+    pub fn refresh(&self) -> impl Future<Output = yts_movies::Result<Response>> + Send + 'static {
+        let page = self.response.page.current;
+        self.fetch((page > 0).then_some(page), false, true)
     }
 
-    fn year_filter(&self, text: &'a str) -> yts_movies::Year {
-        match Self::filter_value(text, " year:") {
-            Some(year) => match year.parse::<u32>() {
-                Ok(n) => yts_movies::Year::Equal(n),
-                _ => yts_movies::Year::All,
-            },
-            _ => yts_movies::Year::All,
+    /// The next page fetched into the cache, so it shows at once.
+    /// Searches by quality are left out, they may look up the torrents of every movie
+    pub fn prefetch_next_page(
+        &self,
+    ) -> Option<impl Future<Output = yts_movies::Result<Response>> + Send + 'static> {
+        if self.cache.is_enabled() && self.query.quality.is_none() {
+            self.next_page()
+        } else {
+            None
         }
     }
 
-    fn order_filter(&self, text: &'a str) -> yts_movies::OrderBy {
-        match Self::filter_value(text, " order:") {
-            Some(o) => {
-                let order: Result<yts_movies::OrderBy, _> = o.try_into();
-                order.unwrap_or(self.default_order.clone())
-            }
-            _ => self.default_order.clone(),
+    /// Pages before the current one, and the last page, skip empty pages backwards
+    pub fn go_to_page(
+        &self,
+        page: u32,
+    ) -> Option<impl Future<Output = yts_movies::Result<Response>> + Send + 'static> {
+        let current = self.response.page.current;
+        let of = self.response.page.of;
+
+        (page >= 1 && page <= of && page != current)
+            .then(|| self.fetch(Some(page), page < current || page == of, false))
+    }
+
+    pub fn next_page(
+        &self,
+    ) -> Option<impl Future<Output = yts_movies::Result<Response>> + Send + 'static> {
+        let response = &self.response;
+        let next_page = response.page.current + 1;
+        if next_page <= response.page.of {
+            Some(self.fetch(Some(next_page), false, false))
+        } else {
+            None
         }
     }
 
-    fn rating_filter(&self, text: &'a str) -> yts_movies::Rating {
-        match Self::filter_value(text, " rating:") {
-            Some(rating) => match rating {
-                "1" => yts_movies::Rating::One,
-                "2" => yts_movies::Rating::Two,
-                "3" => yts_movies::Rating::Three,
-                "4" => yts_movies::Rating::Four,
-                "5" => yts_movies::Rating::Five,
-                "6" => yts_movies::Rating::Six,
-                "7" => yts_movies::Rating::Seven,
-                "8" => yts_movies::Rating::Eight,
-                "9" => yts_movies::Rating::Nine,
-                _ => yts_movies::Rating::All,
-            },
-            _ => yts_movies::Rating::All,
+    pub fn previous_page(
+        &self,
+    ) -> Option<impl Future<Output = yts_movies::Result<Response>> + Send + 'static> {
+        let response = &self.response;
+        let prev_page = response.page.current.saturating_sub(1);
+        if prev_page > 0 {
+            Some(self.fetch(Some(prev_page), true, false))
+        } else {
+            None
         }
     }
 
-    fn filter_value(text: &'a str, filter: &'a str) -> Option<&'a str> {
-        match text.split_once(filter) {
-            Some((_, rest)) => {
-                if let Some((year_str, _)) = rest.split_once(' ') {
-                    Some(year_str)
+    /// Pages left empty by the client side filters are skipped, forwards or backwards,
+    /// so the response page is the YTS page the movies come from.
+    /// Each YTS page is cached already filtered, `fresh` skips the cached ones
+    fn fetch(
+        &self,
+        page: Option<u32>,
+        backwards: bool,
+        fresh: bool,
+    ) -> impl Future<Output = yts_movies::Result<Response>> + Send + 'static {
+        let host = self.host.clone();
+        let query = self.query.clone();
+        let order = query.order(&self.default_order);
+        let cache = self.cache.clone();
+
+        async move {
+            let yts = Yts::new(&host, Duration::from_secs(30));
+            let mut page = page;
+            let mut scanned = 0;
+
+            loop {
+                let key = format!(
+                    "{host}|{}|{:?}|{:?}|{:?}|{:?}|{:?}|{order:?}|{}",
+                    query.title,
+                    query.year,
+                    query.rating,
+                    query.genre,
+                    query.quality,
+                    query.order,
+                    page.unwrap_or(1)
+                );
+
+                let cached = if fresh { None } else { cache.get(&key).await };
+                let response = match cached {
+                    Some(response) => response,
+                    None => {
+                        let mut filters = Filters::default();
+                        filters
+                            .year(query.year())
+                            .rating(query.rating())
+                            .genre(query.genre())
+                            .quality(query.quality())
+                            .order_by(order.clone());
+
+                        if let Some(page) = page {
+                            filters.page(page);
+                        }
+
+                        let mut response = yts
+                            .search_with_filter(&query.title, filters.build())
+                            .await?;
+                        Self::filter(&host, &query, &mut response).await;
+                        cache.put(&key, &response).await;
+                        response
+                    }
+                };
+                scanned += 1;
+
+                let current = response.page.current;
+                let next = if backwards {
+                    current.saturating_sub(1)
                 } else {
-                    Some(rest)
+                    current + 1
+                };
+
+                if !response.movies.is_empty()
+                    || !query.filters_locally()
+                    || scanned >= Self::MAX_SCANNED_PAGES
+                    || next == 0
+                    || next > response.page.of
+                {
+                    return Ok(response);
                 }
+
+                page = Some(next);
             }
-            _ => None,
         }
     }
 
-    fn response_to_rows(&self) -> Vec<Row<'a>> {
+    /// Filters YTS can not apply: year ranges and the x265 codec.
+    /// A movie whose torrents can not be looked up is left out, not the whole page
+    async fn filter(host: &str, query: &Query, response: &mut Response) {
+        if let Some(years) = query.year.filter(|years| !years.is_single()) {
+            response.movies.retain(|movie| years.contains(movie.year));
+        }
+
+        let Some(quality) = query.local_quality() else {
+            return;
+        };
+
+        let permits = Arc::new(Semaphore::new(Self::MAX_TORRENT_LOOKUPS));
+        let mut lookups = JoinSet::new();
+
+        for (i, movie) in std::mem::take(&mut response.movies).into_iter().enumerate() {
+            let host = host.to_string();
+            let quality = quality.to_string();
+            let permits = permits.clone();
+
+            lookups.spawn(async move {
+                let _permit = permits.acquire().await;
+                let yts = Yts::new(&host, Duration::from_secs(30));
+                let matches = yts.torrents(&movie).await.is_ok_and(|torrents| {
+                    torrents.iter().any(|torrent| {
+                        let torrent_quality: &str = (&torrent.quality).into();
+                        torrent_quality.to_lowercase().contains(&quality)
+                    })
+                });
+                (i, matches.then_some(movie))
+            });
+        }
+
+        let mut movies = lookups.join_all().await;
+        movies.sort_by_key(|(i, _)| *i);
+        response.movies = movies.into_iter().filter_map(|(_, movie)| movie).collect();
+    }
+
+    /// The status comes from the downloads and the files of the library
+    fn response_to_rows(&self, columns: &Columns, library: &Library) -> Vec<Row<'static>> {
         let mut rows: Vec<Vec<String>> = Vec::new();
 
         if self.response.page.total == 0 {
@@ -200,71 +369,49 @@ impl<'a> MovieTable<'a> {
                 movie.name.clone(),
                 genres,
                 movie.rating.to_string(),
+                library.status(movie),
             ]);
         }
 
-        rows.iter()
-            .map(|item| Row::new(item.iter().cloned()))
-            .collect::<Vec<_>>()
+        rows.into_iter().map(|item| columns.row(item)).collect()
     }
 
-    pub fn render(&mut self, focus: &Focus) -> (Table<'_>, u16) {
-        let rows = self.response_to_rows();
+    pub fn render(
+        &mut self,
+        focus: &Focus,
+        theme: &Theme,
+        width: u16,
+        library: &Library,
+    ) -> (Table<'_>, u16) {
+        let columns = Columns::fit(&Self::COLUMNS, width, theme.icons.highlight);
+        let rows = self.response_to_rows(&columns, library);
 
         let (header, constraint) = if !rows.is_empty() {
-            (["Year", "Name", "Genre", "Rating"], rows.len() as u16 + 4)
-        } else {
-            (["", "", "", ""], 2)
-        };
-
-        let header = Row::new(header)
-            .style(Style::new().dark_gray().bold())
-            .bottom_margin(0);
-
-        let widths = [
-            Constraint::Percentage(10),
-            Constraint::Percentage(50),
-            Constraint::Percentage(30),
-            Constraint::Percentage(10),
-        ];
-
-        let border_style = if matches!(focus, Focus::MovieTable) {
-            Style::default()
-                .fg(Color::Gray)
-                .add_modifier(Modifier::BOLD)
+            (
+                columns.header(theme.header_style(), Some(&self.sort), &theme.icons),
+                rows.len() as u16 + 4,
+            )
         } else {
-            Style::default().fg(Color::DarkGray)
+            (Row::default(), 2)
         };
 
         (
-            Table::new(rows, widths)
+            Table::new(rows, columns.widths())
                 .header(header)
                 .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_type(BorderType::Thick)
-                        .border_style(border_style)
+                    theme
+                        .panel_block(matches!(focus, Focus::MovieTable))
                         .title(Self::TITLE)
-                        .title_style(Style::new().white().bold())
+                        .title_style(theme.title_style())
                         .title_alignment(ratatui::layout::Alignment::Center)
-                        .title_bottom(self.footer()),
+                        .title_bottom(self.footer_controls(&theme.icons).0),
                 )
                 .column_spacing(1)
-                .style(Style::default().fg(Color::White))
-                .row_highlight_style(
-                    Style::default()
-                        .bg(Color::DarkGray)
-                        .fg(Color::Black)
-                        .add_modifier(Modifier::BOLD),
-                )
-                .column_highlight_style(Color::Gray)
-                .cell_highlight_style(
-                    Style::default()
-                        .bg(Color::DarkGray)
-                        .fg(Color::Black)
-                        .add_modifier(Modifier::BOLD),
-                )
-                .highlight_symbol("  "),
+                .style(theme.text_style())
+                .row_highlight_style(theme.highlight_style())
+                .column_highlight_style(theme.border_focused)
+                .cell_highlight_style(theme.highlight_style())
+                .highlight_symbol(theme.icons.highlight),
             constraint,
         )
     }
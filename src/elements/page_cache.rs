@@ -0,0 +1,228 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use yts_movies::{Movie, Page, Response};
+
+use crate::elements::yts_genre;
+
+/// YTS pages saved on disk by search and page, used until they are older than the TTL
+#[derive(Debug, Clone)]
+pub struct PageCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+/// Movie as saved on disk, the types of yts_movies are not serializable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MovieRecord {
+    pub name: String,
+    pub year: u64,
+    pub rating: f64,
+    pub genres: Vec<String>,
+    pub link: String,
+}
+
+impl From<&Movie> for MovieRecord {
+    fn from(movie: &Movie) -> Self {
+        Self {
+            name: movie.name.clone(),
+            year: movie.year.into(),
+            rating: movie.rating.into(),
+            genres: movie.genres.iter().map(|g| g.to_string()).collect(),
+            link: movie.link.clone(),
+        }
+    }
+}
+
+impl From<MovieRecord> for Movie {
+    /// Genres unknown to YTS are left out
+    fn from(record: MovieRecord) -> Self {
+        Self {
+            name: record.name,
+            year: record.year as _,
+            rating: record.rating as _,
+            genres: record.genres.iter().filter_map(|g| yts_genre(g)).collect(),
+            link: record.link,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    /// Search and page of the response, two keys could share a file name
+    key: String,
+    /// Seconds since the epoch
+    saved: u64,
+    current: u32,
+    of: u32,
+    total: u32,
+    movies: Vec<MovieRecord>,
+}
+
+/// FNV-1a of the key. Unlike DefaultHasher it does not change between Rust releases,
+/// which would leave the saved files behind
+fn hash(key: &str) -> u64 {
+    key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Removes the files of the dir older than the TTL
+fn remove_expired(dir: &Path, ttl: Duration) {
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let expired = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age >= ttl));
+
+        if expired && entry.path().extension().is_some_and(|e| e == "json") {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
+}
+
+impl PageCache {
+    /// A TTL of zero disables the cache
+    pub fn new(dir: PathBuf, ttl: Duration) -> PageCache {
+        Self { dir, ttl }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero()
+    }
+
+    /// File of a search, the key has everything which changes the response
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", hash(key)))
+    }
+
+    /// The saved response, None when it is missing, expired, unreadable or of another key.
+    /// An expired file is removed
+    pub async fn get(&self, key: &str) -> Option<Response> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let path = self.path(key);
+        let json = tokio::task::spawn_blocking({
+            let path = path.clone();
+            move || fs::read(path)
+        })
+        .await
+        .ok()?
+        .ok()?;
+        let entry: Entry = serde_json::from_slice(&json).ok()?;
+
+        if now().saturating_sub(entry.saved) >= self.ttl.as_secs() {
+            let _ = tokio::task::spawn_blocking(move || fs::remove_file(path)).await;
+            return None;
+        }
+
+        (entry.key == key).then(|| Response {
+            page: Page {
+                current: entry.current,
+                of: entry.of,
+                total: entry.total,
+            },
+            movies: entry.movies.into_iter().map(Movie::from).collect(),
+        })
+    }
+
+    /// Saves a response, removing the expired ones.
+    /// A cache which can not be written only makes searches slower
+    pub async fn put(&self, key: &str, response: &Response) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let entry = Entry {
+            key: key.to_string(),
+            saved: now(),
+            current: response.page.current,
+            of: response.page.of,
+            total: response.page.total,
+            movies: response.movies.iter().map(MovieRecord::from).collect(),
+        };
+        let Ok(json) = serde_json::to_vec(&entry) else {
+            return;
+        };
+
+        let dir = self.dir.clone();
+        let path = self.path(key);
+        let ttl = self.ttl;
+        let _ = tokio::task::spawn_blocking(move || {
+            remove_expired(&dir, ttl);
+            fs::create_dir_all(&dir)?;
+            fs::write(path, json)
+        })
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, time::Duration};
+
+    use yts_movies::{Genre, Movie, Page, Response};
+
+    use crate::elements::PageCache;
+
+    #[tokio::test]
+    async fn pages_are_kept_until_the_ttl() {
+        let dir = env::temp_dir().join(format!("gativideo-pages-{}", std::process::id()));
+        let response = Response {
+            page: Page {
+                current: 2,
+                of: 5,
+                total: 100,
+            },
+            movies: vec![Movie {
+                name: String::from("Alien"),
+                year: 1979,
+                rating: 8.5,
+                genres: vec![Genre::Horror, Genre::SciFi],
+                link: String::from("https://yts.lt/movies/alien-1979"),
+            }],
+        };
+
+        let cache = PageCache::new(dir.clone(), Duration::from_secs(60));
+        assert!(cache.get("matrix 2").await.is_none());
+        cache.put("matrix 2", &response).await;
+
+        let cached = cache.get("matrix 2").await.unwrap();
+        assert_eq!(cached.page.current, 2);
+        assert_eq!(cached.movies[0].name, "Alien");
+        assert_eq!(cached.movies[0].genres.len(), 2);
+        assert!(cache.get("matrix 3").await.is_none());
+
+        // A file of another key with the same name is not its page
+        std::fs::copy(cache.path("matrix 2"), cache.path("matrix 3")).unwrap();
+        assert!(cache.get("matrix 3").await.is_none());
+
+        // Expired pages are removed
+        let short = PageCache::new(dir.clone(), Duration::from_secs(1));
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert!(short.get("matrix 2").await.is_none());
+        assert!(!short.path("matrix 2").exists());
+
+        // A TTL of zero disables the cache
+        assert!(
+            PageCache::new(dir.clone(), Duration::from_secs(0))
+                .get("matrix 2")
+                .await
+                .is_none()
+        );
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
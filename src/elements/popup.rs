@@ -2,16 +2,23 @@ use std::{
     fs::File,
     io::{self, Cursor},
     path::Path,
+    time::Duration,
 };
 
 use opensubs::{Filters, Language, OrderBy, Page, Response, SearchBy, Subtitle};
 use ratatui::{
     layout::{Constraint, Flex, Layout, Rect},
-    style::{Color, Modifier, Style},
-    widgets::{Block, BorderType, Borders, Paragraph, Row, ScrollbarState, Table, TableState},
+    style::Style,
+    widgets::{Paragraph, Row, ScrollbarState, Table, TableState},
 };
+use unicode_width::UnicodeWidthStr;
 use yts_movies::{Movie, Torrent, Yts};
 
+use crate::config::Theme;
+use crate::elements::{
+    Column, Columns, Focus, GENRES, InputBox, Sort, SortKey, parse_numbers, parse_size,
+};
+
 pub struct Popup<'a> {
     pub table_state: TableState,
     pub scroll_state: ScrollbarState,
@@ -34,11 +41,12 @@ impl<'a> Popup<'a> {
     }
 
     pub fn centered_area(&self, area: Rect, x: u16, y: u16) -> Rect {
-        let vertical = Layout::vertical([Constraint::Length(y)]).flex(Flex::Center);
-        let horizontal = Layout::horizontal([Constraint::Length(x)]).flex(Flex::Center);
-        let [area] = area.layout(&vertical);
-        let [area] = area.layout(&horizontal);
-        area
+        centered(area, x, y)
+    }
+
+    pub fn select(&mut self, row: usize) {
+        self.table_state.select(Some(row));
+        self.scroll_state = self.scroll_state.position(row);
     }
 
     pub fn scroll_bar_up(&mut self) {
@@ -56,6 +64,15 @@ impl<'a> Popup<'a> {
     }
 }
 
+/// Centered area clamped to the screen, so popups shrink instead of being clipped
+pub fn centered(area: Rect, x: u16, y: u16) -> Rect {
+    let vertical = Layout::vertical([Constraint::Length(y.min(area.height))]).flex(Flex::Center);
+    let horizontal = Layout::horizontal([Constraint::Length(x.min(area.width))]).flex(Flex::Center);
+    let [area] = area.layout(&vertical);
+    let [area] = area.layout(&horizontal);
+    area
+}
+
 pub struct PopupNotification {
     pub text: String,
     pub show: bool,
@@ -70,38 +87,297 @@ impl PopupNotification {
     }
 
     pub fn area(&self, area: Rect) -> Rect {
-        let vertical = Layout::vertical([Constraint::Length(3)]).flex(Flex::Center);
-        let horizontal =
-            Layout::horizontal([Constraint::Length(self.text.len() as u16)]).flex(Flex::Center);
-        let [area] = area.layout(&vertical);
-        let [area] = area.layout(&horizontal);
-        area
+        centered(area, self.text.width() as u16, 3)
+    }
+
+    pub fn render(&self, theme: &Theme) -> Paragraph<'_> {
+        let block = theme.popup_block().title(" Notification ");
+
+        Paragraph::new(self.text.clone())
+            .style(theme.text_style())
+            .block(block)
+    }
+}
+
+pub struct PopupLoading {
+    pub text: String,
+    pub show: bool,
+}
+
+impl PopupLoading {
+    pub fn new() -> PopupLoading {
+        Self {
+            text: String::new(),
+            show: false,
+        }
+    }
+
+    pub fn update(&mut self, pending: Option<(&str, Duration)>, theme: &Theme) {
+        match pending {
+            Some((label, elapsed)) => {
+                let spinner = theme.icons.spinner;
+                let frame = (elapsed.as_millis() / 100) as usize % spinner.len();
+                self.text = format!(
+                    " {}  {label}... {}s (Esc to cancel) ",
+                    spinner[frame],
+                    elapsed.as_secs()
+                );
+                self.show = true;
+            }
+            None => self.show = false,
+        }
+    }
+
+    pub fn area(&self, area: Rect) -> Rect {
+        centered(area, self.text.chars().count() as u16 + 2, 3)
     }
 
-    pub fn render(&self) -> Paragraph<'_> {
-        let block = Block::default()
-            .borders(Borders::ALL)
-            .border_type(BorderType::Plain)
-            .title(" Notification ");
+    pub fn render(&self, theme: &Theme) -> Paragraph<'_> {
+        let block = theme.popup_block().title(" Loading ");
 
         Paragraph::new(self.text.clone())
-            .style(Style::default().fg(Color::White))
+            .style(theme.text_style())
+            .block(block)
+    }
+}
+
+/// Prompt of the page of movies to go to
+pub struct PopupPage {
+    pub text: String,
+    pub show: bool,
+    /// Pages of the search
+    of: u32,
+}
+
+impl PopupPage {
+    const PROMPT: &'static str = " Page: ";
+
+    pub fn new() -> PopupPage {
+        Self {
+            text: String::new(),
+            show: false,
+            of: 0,
+        }
+    }
+
+    pub fn open(&mut self, of: u32) {
+        self.text.clear();
+        self.of = of;
+        self.show = true;
+    }
+
+    /// Only digits are typed, up to the digits of the last page
+    pub fn push(&mut self, c: char) {
+        if c.is_ascii_digit() && self.text.len() < self.of.to_string().len() {
+            self.text.push(c);
+        }
+    }
+
+    pub fn pop(&mut self) {
+        self.text.pop();
+    }
+
+    /// Typed page, None when it is not a page of the search
+    pub fn page(&self) -> Option<u32> {
+        self.text
+            .parse()
+            .ok()
+            .filter(|page| (1..=self.of).contains(page))
+    }
+
+    pub fn area(&self, area: Rect) -> Rect {
+        centered(area, 24, 3)
+    }
+
+    /// Column of the cursor, from the left border
+    pub fn cursor_column(&self) -> u16 {
+        (Self::PROMPT.len() + self.text.len()) as u16 + 1
+    }
+
+    pub fn render(&self, theme: &Theme) -> Paragraph<'_> {
+        let block = theme
+            .popup_block()
+            .title(" Go to page ")
+            .title_bottom(format!(" 1 - {} ", self.of));
+
+        let style = if self.text.is_empty() || self.page().is_some() {
+            theme.text_style()
+        } else {
+            Style::default().fg(theme.error)
+        };
+
+        Paragraph::new(format!("{}{}", Self::PROMPT, self.text))
+            .style(style)
             .block(block)
     }
 }
 
+/// Note of a watchlist entry being written
+pub struct PopupNote {
+    pub input: InputBox,
+    pub show: bool,
+    /// Entry of the watchlist the note belongs to
+    pub index: usize,
+}
+
+impl PopupNote {
+    pub fn new() -> PopupNote {
+        Self {
+            input: InputBox::default(),
+            show: false,
+            index: 0,
+        }
+    }
+
+    pub fn open(&mut self, index: usize, note: &str) {
+        self.input.set_text(note.to_string());
+        self.index = index;
+        self.show = true;
+    }
+
+    pub fn area(&self, area: Rect) -> Rect {
+        centered(area, 60, 3)
+    }
+
+    /// Columns scrolled out on the left, so the cursor stays inside the borders
+    fn scroll(&self, width: u16) -> u16 {
+        self.input
+            .cursor_column()
+            .saturating_sub(width.saturating_sub(3))
+    }
+
+    /// Column of the cursor, from the left border
+    pub fn cursor_column(&self, width: u16) -> u16 {
+        self.input.cursor_column() - self.scroll(width) + 1
+    }
+
+    pub fn render(&self, theme: &Theme, width: u16) -> Paragraph<'_> {
+        let block = theme.popup_block().title(" Note ");
+
+        Paragraph::new(self.input.text())
+            .scroll((0, self.scroll(width)))
+            .style(theme.text_style())
+            .block(block)
+    }
+}
+
+/// Genres of the genre filter, the first row removes the filter
+pub struct PopupGenre<'a> {
+    pub popup: Popup<'a>,
+    /// Focus to go back to when the popup closes
+    pub from: Focus,
+}
+
+impl<'a> PopupGenre<'a> {
+    const ALL: &'static str = "All genres";
+
+    pub fn new() -> PopupGenre<'a> {
+        Self {
+            popup: Popup::new(" Genres "),
+            from: Focus::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        GENRES.len() + 1
+    }
+
+    /// Shows the popup with the genre of the current search selected
+    pub fn open(&mut self, from: Focus, current: Option<&str>) {
+        let row = current
+            .and_then(|genre| GENRES.iter().position(|&g| g == genre))
+            .map_or(0, |position| position + 1);
+
+        self.popup.select(row);
+        self.popup.show = true;
+        self.from = from;
+    }
+
+    /// Selected genre, Some(None) for all genres
+    pub fn selected(&self) -> Option<Option<&'static str>> {
+        match self.popup.table_state.selected()? {
+            0 => Some(None),
+            row => GENRES.get(row - 1).map(|&genre| Some(genre)),
+        }
+    }
+
+    pub fn area(&self, area: Rect) -> Rect {
+        self.popup.centered_area(area, 30, self.len() as u16 + 3)
+    }
+
+    pub fn render(&self, theme: &Theme) -> Table<'a> {
+        let rows = std::iter::once(Self::ALL)
+            .chain(GENRES)
+            .map(|genre| Row::new([genre]));
+
+        Table::new(rows, [Constraint::Percentage(100)])
+            .header(Row::new(["Genre"]).style(theme.header_style()))
+            .block(
+                theme
+                    .popup_block()
+                    .title(self.popup.title)
+                    .title_style(theme.title_style())
+                    .title_alignment(ratatui::layout::Alignment::Center),
+            )
+            .style(theme.text_style())
+            .row_highlight_style(theme.highlight_style())
+            .highlight_symbol(theme.icons.popup_highlight)
+    }
+}
+
 pub struct PopupTorrent<'a> {
     pub popup: Popup<'a>,
     pub torrents: Vec<Torrent>,
-    yts: Yts<'a>,
+    pub sort: Sort,
+    host: String,
 }
 
 impl<'a> PopupTorrent<'a> {
-    pub fn new() -> PopupTorrent<'a> {
+    pub fn new(host: &str) -> PopupTorrent<'a> {
         Self {
             popup: Popup::new(" Torrents "),
-            yts: Yts::default(),
             torrents: vec![],
+            sort: Sort::default(),
+            host: host.to_string(),
+        }
+    }
+
+    /// Quality, size and seeds
+    const SORTABLE: [usize; 3] = [0, 1, 4];
+
+    /// Shows the torrents of a movie in the current order
+    pub fn set_torrents(&mut self, mut torrents: Vec<Torrent>) {
+        self.sort.reset(&mut torrents, Self::sort_key);
+        self.torrents = torrents;
+        self.popup.select(0);
+    }
+
+    /// Sorts by the next column, the selected torrent stays selected
+    pub fn sort_next(&mut self) {
+        let selected = self.sort.next(
+            &Self::SORTABLE,
+            &mut self.torrents,
+            Self::sort_key,
+            self.popup.table_state.selected(),
+        );
+        self.popup.select(selected.unwrap_or_default());
+    }
+
+    /// Seeds are the last number of the peers/seeds column
+    fn sort_key(torrent: &Torrent, column: usize) -> SortKey {
+        match column {
+            0 => {
+                let quality: &str = (&torrent.quality).into();
+                SortKey::Number(parse_numbers(quality).first().copied().unwrap_or_default())
+            }
+            1 => SortKey::Number(parse_size(&torrent.size)),
+            4 => SortKey::Number(
+                parse_numbers(&torrent.peers_seeds)
+                    .last()
+                    .copied()
+                    .unwrap_or_default(),
+            ),
+            _ => SortKey::text(&torrent.language),
         }
     }
 
@@ -109,23 +385,31 @@ impl<'a> PopupTorrent<'a> {
         self.popup.centered_area(area, 70, 5)
     }
 
-    pub async fn search_torrents(&mut self, movie: &Movie) -> yts_movies::Result {
-        self.torrents = self.yts.torrents(movie).await?;
-        Ok(())
+    pub fn search_torrents(
+        &self,
+        movie: &Movie,
+    ) -> impl Future<Output = yts_movies::Result<Vec<Torrent>>> + Send + 'static {
+        let movie = movie.clone();
+        let host = self.host.clone();
+
+        async move {
+            Yts::new(&host, Duration::from_secs(30))
+                .torrents(&movie)
+                .await
+        }
     }
 
-    pub fn render(&self) -> Table<'a> {
-        let widths = [
-            Constraint::Percentage(15),
-            Constraint::Percentage(15),
-            Constraint::Percentage(30),
-            Constraint::Percentage(20),
-            Constraint::Percentage(20),
-        ];
+    const COLUMNS: [Column; 5] = [
+        Column::new("Quality", 15, 7, 0),
+        Column::new("Size", 15, 8, 1),
+        Column::new("Language", 30, 8, 4),
+        Column::new("Runtime", 20, 8, 3),
+        Column::new("Peers/Seeds", 20, 11, 2),
+    ];
 
-        let header = Row::new(["Quality", "Size", "Language", "Runtime", "Peers/Seeds"])
-            .style(Style::new().dark_gray().bold())
-            .bottom_margin(0);
+    pub fn render(&self, theme: &Theme, width: u16) -> Table<'a> {
+        let columns = Columns::fit(&Self::COLUMNS, width, theme.icons.popup_highlight);
+        let header = columns.header(theme.header_style(), Some(&self.sort), &theme.icons);
 
         let mut rows: Vec<Vec<String>> = Vec::new();
 
@@ -143,39 +427,28 @@ impl<'a> PopupTorrent<'a> {
         }
 
         let rows = rows
-            .iter()
-            .map(|item| Row::new(item.iter().cloned()))
+            .into_iter()
+            .map(|item| columns.row(item))
             .collect::<Vec<_>>();
 
         let footer = format!(" {} torrent/s ", rows.len());
 
-        Table::new(rows, widths)
+        Table::new(rows, columns.widths())
             .header(header)
             .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Plain)
+                theme
+                    .popup_block()
                     .title(self.popup.title)
-                    .title_style(Style::new().white().bold())
+                    .title_style(theme.title_style())
                     .title_alignment(ratatui::layout::Alignment::Center)
                     .title_bottom(footer),
             )
             .column_spacing(1)
-            .style(Style::default().fg(Color::White))
-            .row_highlight_style(
-                Style::default()
-                    .bg(Color::DarkGray)
-                    .fg(Color::Black)
-                    .add_modifier(Modifier::BOLD),
-            )
-            .column_highlight_style(Color::Gray)
-            .cell_highlight_style(
-                Style::default()
-                    .bg(Color::DarkGray)
-                    .fg(Color::Black)
-                    .add_modifier(Modifier::BOLD),
-            )
-            .highlight_symbol(" ")
+            .style(theme.text_style())
+            .row_highlight_style(theme.highlight_style())
+            .column_highlight_style(theme.border_focused)
+            .cell_highlight_style(theme.highlight_style())
+            .highlight_symbol(theme.icons.popup_highlight)
     }
 }
 
@@ -183,6 +456,7 @@ pub struct PopupSubtitle<'a> {
     pub popup: Popup<'a>,
     pub subtitles: Vec<Subtitle>,
     pub page: Page,
+    pub sort: Sort,
     languages: &'a [Language],
     order: OrderBy,
     download_dir: &'a str,
@@ -201,6 +475,38 @@ impl<'a> PopupSubtitle<'a> {
             subtitles: vec![],
             download_dir,
             page: Self::empty_page(),
+            sort: Sort::default(),
+        }
+    }
+
+    /// Movie, language, downloads and rating
+    const SORTABLE: [usize; 4] = [1, 2, 5, 6];
+
+    /// Shows the subtitles of a movie in the current order
+    pub fn set_subtitles(&mut self, page: Page, mut subtitles: Vec<Subtitle>) {
+        self.sort.reset(&mut subtitles, Self::sort_key);
+        self.subtitles = subtitles;
+        self.page = page;
+        self.popup.select(0);
+    }
+
+    /// Sorts by the next column, the selected subtitle stays selected
+    pub fn sort_next(&mut self) {
+        let selected = self.sort.next(
+            &Self::SORTABLE,
+            &mut self.subtitles,
+            Self::sort_key,
+            self.popup.table_state.selected(),
+        );
+        self.popup.select(selected.unwrap_or_default());
+    }
+
+    fn sort_key(subtitle: &Subtitle, column: usize) -> SortKey {
+        match column {
+            2 => SortKey::text(&subtitle.language),
+            5 => SortKey::Number(subtitle.downloads as f64),
+            6 => SortKey::Number(subtitle.rating as f64),
+            _ => SortKey::text(&subtitle.movie),
         }
     }
 
@@ -209,55 +515,62 @@ impl<'a> PopupSubtitle<'a> {
         self.popup.centered_area(area, 120, y)
     }
 
-    pub async fn search_subtitles(&mut self, movie: &Movie) -> opensubs::Result {
-        self.subtitles.clear();
-        self.page = Self::empty_page();
-
-        let name = &movie.name;
+    pub fn search_subtitles(
+        &self,
+        movie: &Movie,
+    ) -> impl Future<Output = opensubs::Result<(Page, Vec<Subtitle>)>> + Send + 'static {
+        let name = movie.name.clone();
         let year = movie.year;
-        let results = opensubs::search(SearchBy::MovieAndFilter(
-            &movie.name,
-            Filters::default()
-                .year(movie.year)
-                .languages(self.languages)
-                .order_by(self.order.clone())
-                .build(),
-        ))
-        .await?;
-
-        match results {
-            Response::Movie(movies) => {
-                if let Some(movie) = movies.iter().find(|&movie| {
-                    movie.name.to_lowercase() == format!("{} ({})", name.to_lowercase(), year)
-                }) && let Response::Subtitle(page, subtitles) =
-                    opensubs::search(SearchBy::Url(&movie.subtitles_link)).await?
-                {
-                    self.subtitles = subtitles;
-                    self.page = page;
+        let languages = self.languages.to_vec();
+        let order = self.order.clone();
+
+        async move {
+            let results = opensubs::search(SearchBy::MovieAndFilter(
+                &name,
+                Filters::default()
+                    .year(year)
+                    .languages(&languages)
+                    .order_by(order)
+                    .build(),
+            ))
+            .await?;
+
+            match results {
+                Response::Movie(movies) => {
+                    if let Some(movie) = movies.iter().find(|&movie| {
+                        movie.name.to_lowercase() == format!("{} ({})", name.to_lowercase(), year)
+                    }) && let Response::Subtitle(page, subtitles) =
+                        opensubs::search(SearchBy::Url(&movie.subtitles_link)).await?
+                    {
+                        return Ok((page, subtitles));
+                    }
+                    Ok((Self::empty_page(), vec![]))
                 }
-            }
-            Response::Subtitle(page, subtitles) => {
-                self.subtitles = subtitles;
-                self.page = page;
+                Response::Subtitle(page, subtitles) => Ok((page, subtitles)),
             }
         }
-
-        Ok(())
     }
 
-    pub async fn download_subtitle(&self, link: &str, movie_name: &str) -> anyhow::Result<()> {
+    pub fn download_subtitle(
+        &self,
+        link: &str,
+        movie_name: &str,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send + 'static {
         let file_name = format!("{movie_name}.srt");
         let output = Path::new(self.download_dir).join(&file_name);
+        let link = link.to_string();
 
-        let response = reqwest::get(link).await?;
-        let zip_bytes = response.bytes().await?.to_vec();
+        async move {
+            let response = reqwest::get(link).await?;
+            let zip_bytes = response.bytes().await?.to_vec();
 
-        self.save_first_srt(&zip_bytes, &output)?;
+            Self::save_first_srt(&zip_bytes, &output)?;
 
-        Ok(())
+            Ok(())
+        }
     }
 
-    fn save_first_srt(&self, zip_data: &[u8], output: &Path) -> Result<(), io::Error> {
+    fn save_first_srt(zip_data: &[u8], output: &Path) -> Result<(), io::Error> {
         let cursor = Cursor::new(zip_data);
         let mut archive = zip::ZipArchive::new(cursor)?;
 
@@ -277,34 +590,26 @@ impl<'a> PopupSubtitle<'a> {
         Ok(())
     }
 
-    pub fn render(&self) -> Table<'a> {
-        let widths = [
-            Constraint::Percentage(5),
-            Constraint::Percentage(35),
-            Constraint::Percentage(20),
-            Constraint::Percentage(5),
-            Constraint::Percentage(15),
-            Constraint::Percentage(10),
-            Constraint::Percentage(10),
-        ];
-
-        let header = Row::new([
-            "#",
-            "Movie",
-            "Language",
-            "CD",
-            "Uploaded",
-            "Downloads",
-            "Rating",
-        ])
-        .style(Style::new().dark_gray().bold())
-        .bottom_margin(0);
+    const COLUMNS: [Column; 7] = [
+        Column::new("#", 5, 2, 1),
+        Column::new("Movie", 35, 20, 0),
+        Column::new("Language", 20, 8, 2),
+        Column::new("CD", 5, 2, 6),
+        Column::new("Uploaded", 15, 10, 5),
+        Column::new("Downloads", 10, 9, 3),
+        Column::new("Rating", 10, 6, 4),
+    ];
+
+    pub fn render(&self, theme: &Theme, width: u16) -> Table<'a> {
+        let columns = Columns::fit(&Self::COLUMNS, width, theme.icons.popup_highlight);
+        let header = columns.header(theme.header_style(), Some(&self.sort), &theme.icons);
 
         let mut rows: Vec<Vec<String>> = Vec::new();
 
         for (i, sub) in self.subtitles.iter().enumerate() {
+            // The number is the position given by opensubtitles
             rows.push(vec![
-                (i + 1).to_string(),
+                (self.sort.rank(i) + 1).to_string(),
                 sub.movie.clone(),
                 sub.language.clone(),
                 sub.cd.clone(),
@@ -315,37 +620,26 @@ impl<'a> PopupSubtitle<'a> {
         }
 
         let rows = rows
-            .iter()
-            .map(|item| Row::new(item.iter().cloned()))
+            .into_iter()
+            .map(|item| columns.row(item))
             .collect::<Vec<_>>();
 
-        Table::new(rows, widths)
+        Table::new(rows, columns.widths())
             .header(header)
             .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Plain)
+                theme
+                    .popup_block()
                     .title(self.popup.title)
-                    .title_style(Style::new().white().bold())
+                    .title_style(theme.title_style())
                     .title_alignment(ratatui::layout::Alignment::Center)
                     .title_bottom(self.footer()),
             )
             .column_spacing(1)
-            .style(Style::default().fg(Color::White))
-            .row_highlight_style(
-                Style::default()
-                    .bg(Color::DarkGray)
-                    .fg(Color::Black)
-                    .add_modifier(Modifier::BOLD),
-            )
-            .column_highlight_style(Color::Gray)
-            .cell_highlight_style(
-                Style::default()
-                    .bg(Color::DarkGray)
-                    .fg(Color::Black)
-                    .add_modifier(Modifier::BOLD),
-            )
-            .highlight_symbol(" ")
+            .style(theme.text_style())
+            .row_highlight_style(theme.highlight_style())
+            .column_highlight_style(theme.border_focused)
+            .cell_highlight_style(theme.highlight_style())
+            .highlight_symbol(theme.icons.popup_highlight)
     }
 
     fn footer(&self) -> String {
@@ -372,12 +666,23 @@ impl<'a> PopupSubtitle<'a> {
 #[cfg(test)]
 mod tests {
     use opensubs::Language;
+    use ratatui::layout::Rect;
 
-    use crate::elements::PopupSubtitle;
+    use crate::elements::{PopupNotification, PopupSubtitle};
+
+    #[test]
+    fn notification_as_wide_as_its_text() {
+        let mut notification = PopupNotification::new();
+        notification.text = String::from("Película añadida 🎬");
+
+        let area = notification.area(Rect::new(0, 0, 80, 24));
+
+        assert_eq!(area.width, 19);
+    }
 
     #[tokio::test]
     async fn search_subtitles() {
-        let mut popup_subtitle =
+        let popup_subtitle =
             PopupSubtitle::new(&[Language::Spanish], opensubs::OrderBy::Rating, "");
 
         let response = yts_movies::Yts::default()
@@ -387,8 +692,8 @@ mod tests {
 
         let movie = response.movies.first().unwrap();
 
-        popup_subtitle.search_subtitles(movie).await.unwrap();
+        let (_, subtitles) = popup_subtitle.search_subtitles(movie).await.unwrap();
 
-        assert!(!popup_subtitle.subtitles.is_empty());
+        assert!(!subtitles.is_empty());
     }
 }
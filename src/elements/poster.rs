@@ -0,0 +1,382 @@
+use std::{env, fs, io::Write, path::PathBuf, sync::Arc};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use crossterm::{
+    cursor::{MoveTo, RestorePosition, SavePosition},
+    queue,
+    style::Print,
+    terminal,
+};
+use image::{RgbImage, imageops::FilterType};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+
+/// How posters are drawn. Configured in `[ui] posters`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Graphics {
+    Kitty,
+    Sixel,
+    HalfBlocks,
+    Off,
+}
+
+impl Graphics {
+    /// Terminals known by their environment to show images, half blocks elsewhere
+    pub fn detect() -> Graphics {
+        let var = |name: &str| env::var(name).unwrap_or_default();
+        let term = var("TERM");
+        let program = var("TERM_PROGRAM");
+
+        // Images need a passthrough inside tmux
+        if env::var_os("TMUX").is_some() {
+            Graphics::HalfBlocks
+        } else if env::var_os("KITTY_WINDOW_ID").is_some()
+            || term.contains("kitty")
+            || term.contains("ghostty")
+            || matches!(program.as_str(), "WezTerm" | "ghostty")
+        {
+            Graphics::Kitty
+        } else if ["foot", "mlterm", "yaft", "sixel"]
+            .iter()
+            .any(|name| term.contains(name))
+            || program == "iTerm.app"
+        {
+            Graphics::Sixel
+        } else {
+            Graphics::HalfBlocks
+        }
+    }
+}
+
+impl TryFrom<&str> for Graphics {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "auto" => Ok(Graphics::detect()),
+            "kitty" => Ok(Graphics::Kitty),
+            "sixel" => Ok(Graphics::Sixel),
+            "half-blocks" | "halfblocks" => Ok(Graphics::HalfBlocks),
+            "off" => Ok(Graphics::Off),
+            _ => Err(format!(
+                "'{value}' could be auto, kitty, sixel, half-blocks or off"
+            )),
+        }
+    }
+}
+
+/// Cover of a movie from the disk cache, downloaded and saved when missing.
+/// The files are read and written off the async runtime
+pub async fn load_poster(
+    client: &reqwest::Client,
+    dir: PathBuf,
+    name: &str,
+    url: &str,
+) -> anyhow::Result<RgbImage> {
+    let path = dir.join(format!("{name}.jpg"));
+
+    let cached = tokio::task::spawn_blocking({
+        let path = path.clone();
+        move || fs::read(path)
+    })
+    .await?;
+
+    let bytes = match cached {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            let bytes = client
+                .get(url)
+                .send()
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?
+                .to_vec();
+
+            // A poster which can not be cached is still shown
+            if !name.is_empty() {
+                let file = bytes.clone();
+                let _ = tokio::task::spawn_blocking(move || {
+                    fs::create_dir_all(&dir)?;
+                    fs::write(path, file)
+                })
+                .await;
+            }
+            bytes
+        }
+    };
+
+    Ok(image::load_from_memory(&bytes)?.to_rgb8())
+}
+
+/// Size of the image inside a box, keeping its aspect ratio
+fn fit(image: &RgbImage, width: u32, height: u32) -> (u32, u32) {
+    let (w, h) = image.dimensions();
+    let scale = (width as f64 / w.max(1) as f64).min(height as f64 / h.max(1) as f64);
+
+    (
+        ((w as f64 * scale) as u32).max(1),
+        ((h as f64 * scale) as u32).max(1),
+    )
+}
+
+/// Each cell shows two pixels: the upper one as the color of '▀' and the lower one as background
+fn half_blocks(image: &RgbImage, area: Rect) -> Vec<Line<'static>> {
+    let (width, height) = fit(image, area.width as u32, area.height as u32 * 2);
+    let image = image::imageops::resize(image, width, height, FilterType::Triangle);
+    let color = |x, y| {
+        let [r, g, b] = image.get_pixel(x, y).0;
+        Color::Rgb(r, g, b)
+    };
+
+    (0..height)
+        .step_by(2)
+        .map(|y| {
+            Line::from(
+                (0..width)
+                    .map(|x| {
+                        let style = Style::default().fg(color(x, y));
+                        let style = if y + 1 < height {
+                            style.bg(color(x, y + 1))
+                        } else {
+                            style
+                        };
+                        Span::styled("▀", style)
+                    })
+                    .collect::<Vec<Span>>(),
+            )
+        })
+        .collect()
+}
+
+/// Pixels of a cell, from the terminal or a common size
+fn cell_size() -> (u32, u32) {
+    match terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => (
+            (size.width / size.columns) as u32,
+            (size.height / size.rows) as u32,
+        ),
+        _ => (10, 20),
+    }
+}
+
+const KITTY_DELETE: &str = "\x1b_Ga=d,d=A,q=2\x1b\\";
+
+/// Raw RGB pixels sent in chunks, placed at the cursor without moving it
+fn kitty(image: &RgbImage, area: Rect) -> String {
+    let (cell_width, cell_height) = cell_size();
+    let (width, height) = fit(
+        image,
+        area.width as u32 * cell_width,
+        area.height as u32 * cell_height,
+    );
+    let image = image::imageops::resize(image, width, height, FilterType::Triangle);
+    let data = STANDARD.encode(image.as_raw());
+
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(4096).collect();
+    let mut sequence = String::new();
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = if i + 1 < chunks.len() { 1 } else { 0 };
+        let chunk = String::from_utf8_lossy(chunk);
+        if i == 0 {
+            sequence.push_str(&format!(
+                "\x1b_Ga=T,f=24,s={width},v={height},q=2,C=1,m={more};{chunk}\x1b\\"
+            ));
+        } else {
+            sequence.push_str(&format!("\x1b_Gm={more};{chunk}\x1b\\"));
+        }
+    }
+
+    sequence
+}
+
+fn sixel(image: &RgbImage, area: Rect) -> anyhow::Result<String> {
+    let (cell_width, cell_height) = cell_size();
+    let (width, height) = fit(
+        image,
+        area.width as u32 * cell_width,
+        area.height as u32 * cell_height,
+    );
+    let image = image::imageops::resize(image, width, height, FilterType::Triangle);
+    let rgba = image::DynamicImage::ImageRgb8(image).to_rgba8();
+
+    Ok(
+        icy_sixel::SixelImage::from_rgba(rgba.into_raw(), width as usize, height as usize)
+            .encode()?,
+    )
+}
+
+struct Placement {
+    link: String,
+    area: Rect,
+    image: Arc<RgbImage>,
+}
+
+impl Placement {
+    fn same(&self, other: &Placement) -> bool {
+        self.link == other.link && self.area == other.area
+    }
+}
+
+/// Poster of the details pane. Half blocks are cells of the frame, kitty and sixel images
+/// are written to the terminal after the frame, only when they change
+pub struct Poster {
+    pub graphics: Graphics,
+    /// Half blocks of the last poster, by movie link and area
+    lines: Option<(String, Rect, Vec<Line<'static>>)>,
+    /// Image of this frame and image on the screen
+    wanted: Option<Placement>,
+    drawn: Option<Placement>,
+    /// Columns and rows when the image was drawn
+    screen: Option<(u16, u16)>,
+}
+
+impl Poster {
+    pub fn new(graphics: Graphics) -> Poster {
+        Self {
+            graphics,
+            lines: None,
+            wanted: None,
+            drawn: None,
+            screen: None,
+        }
+    }
+
+    /// Half blocks for the frame, or None when the image is drawn after it over a cleared area.
+    /// Images are not drawn while popups are open, as they would cover them
+    pub fn render(
+        &mut self,
+        link: &str,
+        image: &Arc<RgbImage>,
+        area: Rect,
+        images: bool,
+    ) -> Option<Paragraph<'static>> {
+        if images && matches!(self.graphics, Graphics::Kitty | Graphics::Sixel) {
+            self.wanted = Some(Placement {
+                link: link.to_string(),
+                area,
+                image: image.clone(),
+            });
+            return None;
+        }
+
+        let cached = self
+            .lines
+            .as_ref()
+            .is_some_and(|(l, a, _)| l == link && *a == area);
+        if !cached {
+            self.lines = Some((link.to_string(), area, half_blocks(image, area)));
+        }
+
+        self.lines
+            .as_ref()
+            .map(|(_, _, lines)| Paragraph::new(lines.clone()))
+    }
+
+    /// Writes the image of the last frame if it changed. Returns whether the whole screen
+    /// has to be drawn again to remove an old sixel image
+    pub fn flush(&mut self, out: &mut impl Write) -> anyhow::Result<bool> {
+        let wanted = self.wanted.take();
+
+        // A resize clears the screen, the image is drawn again
+        let screen = terminal::size().ok();
+        if screen != self.screen {
+            self.screen = screen;
+            if self.drawn.take().is_some() && self.graphics == Graphics::Kitty {
+                queue!(out, Print(KITTY_DELETE))?;
+            }
+        }
+
+        match (&self.drawn, &wanted) {
+            (Some(drawn), Some(wanted)) if drawn.same(wanted) => return Ok(false),
+            (None, None) => return Ok(false),
+            _ => {}
+        }
+
+        let old = self.drawn.take();
+
+        let sequence = match self.graphics {
+            Graphics::Kitty => {
+                queue!(out, Print(KITTY_DELETE))?;
+                wanted
+                    .as_ref()
+                    .map(|placement| kitty(&placement.image, placement.area))
+            }
+            Graphics::Sixel => {
+                // Sixel pixels stay until their cells are written again
+                match (old, &wanted) {
+                    (Some(old), Some(wanted)) if old.area == wanted.area => {
+                        let blank = " ".repeat(old.area.width as usize);
+                        for y in old.area.top()..old.area.bottom() {
+                            queue!(out, MoveTo(old.area.x, y), Print(&blank))?;
+                        }
+                    }
+                    (Some(_), _) => return Ok(true),
+                    _ => {}
+                }
+
+                match &wanted {
+                    Some(placement) => Some(sixel(&placement.image, placement.area)?),
+                    None => None,
+                }
+            }
+            _ => None,
+        };
+
+        if let (Some(sequence), Some(placement)) = (sequence, &wanted) {
+            queue!(
+                out,
+                SavePosition,
+                MoveTo(placement.area.x, placement.area.y),
+                Print(sequence),
+                RestorePosition
+            )?;
+        }
+        out.flush()?;
+
+        self.drawn = wanted;
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use image::{Rgb, RgbImage};
+    use ratatui::layout::Rect;
+
+    use crate::elements::{Graphics, Poster};
+
+    #[test]
+    fn half_blocks_and_images_after_the_frame() {
+        let image = Arc::new(RgbImage::from_pixel(20, 30, Rgb([200, 10, 10])));
+        let area = Rect::new(1, 1, 8, 6);
+
+        let mut poster = Poster::new(Graphics::HalfBlocks);
+        let lines = poster.render("matrix", &image, area, true);
+        assert!(lines.is_some());
+        assert_eq!(poster.lines.as_ref().unwrap().2.len(), 6);
+
+        let mut poster = Poster::new(Graphics::Kitty);
+        assert!(poster.render("matrix", &image, area, false).is_some());
+        assert!(poster.render("matrix", &image, area, true).is_none());
+
+        let mut out = vec![];
+        assert!(!poster.flush(&mut out).unwrap());
+        assert!(String::from_utf8_lossy(&out).contains("a=T,f=24"));
+
+        // Unchanged images are not sent again, hidden ones are deleted
+        out.clear();
+        poster.render("matrix", &image, area, true);
+        poster.flush(&mut out).unwrap();
+        assert!(out.is_empty());
+        poster.flush(&mut out).unwrap();
+        assert_eq!(String::from_utf8_lossy(&out), super::KITTY_DELETE);
+    }
+}
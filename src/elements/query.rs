@@ -0,0 +1,562 @@
+use std::{fmt, ops::Range};
+
+/// Filters of the search box, in the order they are completed
+pub const FILTERS: [&str; 5] = ["year", "rating", "order", "genre", "quality"];
+
+pub const ORDERS: [&str; 7] = [
+    "latest",
+    "oldest",
+    "rating",
+    "alphabetical",
+    "featured",
+    "year",
+    "likes",
+];
+
+pub const RATINGS: [&str; 9] = ["1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+pub const GENRES: [&str; 26] = [
+    "action",
+    "adventure",
+    "animation",
+    "biography",
+    "comedy",
+    "crime",
+    "documentary",
+    "drama",
+    "family",
+    "fantasy",
+    "film-noir",
+    "game-show",
+    "history",
+    "horror",
+    "music",
+    "musical",
+    "mystery",
+    "news",
+    "reality-tv",
+    "romance",
+    "sci-fi",
+    "sport",
+    "talk-show",
+    "thriller",
+    "war",
+    "western",
+];
+
+/// Qualities and codecs of the YTS torrents
+pub const QUALITIES: [&str; 6] = ["480p", "720p", "1080p", "2160p", "3d", "x265"];
+
+/// Search of the movie table: a title plus filters in any position.
+/// Ex: `"the thing" year:1982 order:latest genre:horror`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Query {
+    pub title: String,
+    pub year: Option<Years>,
+    pub rating: Option<u8>,
+    pub order: Option<String>,
+    pub genre: Option<String>,
+    pub quality: Option<String>,
+}
+
+/// Inclusive range of the year filter: `1999`, `2010-2015`, `>2000`, `<1970` or `80s`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Years {
+    pub from: u32,
+    pub to: u32,
+}
+
+impl Years {
+    const FIRST: u32 = 1900;
+    const LAST: u32 = 2100;
+
+    pub fn single(year: u32) -> Years {
+        Self {
+            from: year,
+            to: year,
+        }
+    }
+
+    pub fn contains(&self, year: u32) -> bool {
+        (self.from..=self.to).contains(&year)
+    }
+
+    /// YTS only filters by one year, ranges are filtered on the client
+    pub fn is_single(&self) -> bool {
+        self.from == self.to
+    }
+
+    fn parse(value: &str) -> Option<Years> {
+        let year = |text: &str| {
+            text.trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|year| (Self::FIRST..=Self::LAST).contains(year))
+        };
+
+        let years = if let Some(from) = value.strip_prefix(">=") {
+            Self {
+                from: year(from)?,
+                to: Self::LAST,
+            }
+        } else if let Some(from) = value.strip_prefix('>') {
+            Self {
+                from: year(from)? + 1,
+                to: Self::LAST,
+            }
+        } else if let Some(to) = value.strip_prefix("<=") {
+            Self {
+                from: Self::FIRST,
+                to: year(to)?,
+            }
+        } else if let Some(to) = value.strip_prefix('<') {
+            Self {
+                from: Self::FIRST,
+                to: year(to)?.checked_sub(1)?,
+            }
+        } else if let Some(decade) = value.strip_suffix(['s', 'S']) {
+            // "80s" are the 1980s, "10s" the 2010s, "1920s" is written in full
+            let start = match decade.len() {
+                2 => match decade.parse::<u32>().ok().filter(|d| d % 10 == 0)? {
+                    d if d < 30 => 2000 + d,
+                    d => 1900 + d,
+                },
+                4 => year(decade).filter(|d| d % 10 == 0)?,
+                _ => return None,
+            };
+            Self {
+                from: start,
+                to: start + 9,
+            }
+        } else if let Some((from, to)) = value.split_once('-') {
+            Self {
+                from: year(from)?,
+                to: year(to)?,
+            }
+        } else {
+            Self::single(year(value)?)
+        };
+
+        (years.from <= years.to).then_some(years)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    /// Bytes of the invalid token in the search text
+    pub span: Range<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+struct Token {
+    text: String,
+    span: Range<usize>,
+    /// Starts with a quote, so it is part of the title even with a ':'
+    quoted: bool,
+}
+
+/// Words split by whitespace. Double quotes group words and are removed
+fn tokenize(text: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = vec![];
+    let mut chars = text.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let mut word = String::new();
+        let mut in_quotes = false;
+        let mut end = text.len();
+
+        while let Some(&(i, c)) = chars.peek() {
+            if c.is_whitespace() && !in_quotes {
+                end = i;
+                break;
+            }
+            if c == '"' {
+                in_quotes = !in_quotes;
+            } else {
+                word.push(c);
+            }
+            chars.next();
+        }
+
+        if in_quotes {
+            return Err(QueryError {
+                span: start..text.len(),
+                message: String::from("missing closing quote"),
+            });
+        }
+
+        tokens.push(Token {
+            text: word,
+            span: start..end,
+            quoted: c == '"',
+        });
+    }
+
+    Ok(tokens)
+}
+
+fn one_of(value: &str, allowed: &[&str], filter: &str) -> Result<String, String> {
+    let value = value.to_lowercase();
+    if allowed.contains(&value.as_str()) {
+        Ok(value)
+    } else {
+        Err(format!(
+            "{filter}:{value} is not valid, it could be {}",
+            allowed.join(", ")
+        ))
+    }
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Query, Vec<QueryError>> {
+        let tokens = tokenize(text).map_err(|e| vec![e])?;
+        let mut query = Query::default();
+        let mut title = vec![];
+        let mut errors = vec![];
+
+        for token in tokens {
+            // Quoted words are always part of the title, so are unknown "word:" like "Mission:"
+            let filter = match token.text.split_once(':') {
+                Some((key, value)) if !token.quoted => FILTERS
+                    .iter()
+                    .find(|&&filter| filter == key.to_lowercase())
+                    .map(|&filter| (filter, value)),
+                _ => None,
+            };
+
+            let Some((filter, value)) = filter else {
+                title.push(token.text);
+                continue;
+            };
+
+            if let Err(message) = query.set(filter, value) {
+                errors.push(QueryError {
+                    span: token.span,
+                    message,
+                });
+            }
+        }
+
+        query.title = title.join(" ");
+
+        if errors.is_empty() {
+            Ok(query)
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn set(&mut self, filter: &str, value: &str) -> Result<(), String> {
+        if value.is_empty() {
+            return Err(format!("{filter}: needs a value"));
+        }
+
+        match filter {
+            "year" => self.year = Some(Years::parse(value).ok_or_else(|| {
+                format!(
+                    "year:{value} is not valid, it could be 1999, 2010-2015, >2000, <1970 or 80s"
+                )
+            })?),
+            "rating" => self.rating = one_of(value, &RATINGS, filter)?.parse().ok(),
+            "order" => self.order = Some(one_of(value, &ORDERS, filter)?),
+            "genre" => self.genre = Some(one_of(value, &GENRES, filter)?),
+            "quality" => self.quality = Some(one_of(value, &QUALITIES, filter)?),
+            _ => return Err(format!("unknown filter {filter}:")),
+        }
+
+        Ok(())
+    }
+
+    pub fn year(&self) -> yts_movies::Year {
+        match self.year {
+            Some(years) if years.is_single() => yts_movies::Year::Equal(years.from),
+            _ => yts_movies::Year::All,
+        }
+    }
+
+    /// Whether some filter is applied to the YTS pages instead of by YTS
+    pub fn filters_locally(&self) -> bool {
+        self.year.is_some_and(|years| !years.is_single()) || self.local_quality().is_some()
+    }
+
+    pub fn genre(&self) -> yts_movies::Genre {
+        self.genre
+            .as_deref()
+            .and_then(yts_genre)
+            .unwrap_or(yts_movies::Genre::All)
+    }
+
+    /// YTS filters resolutions only, the codec is looked up in the torrents
+    pub fn quality(&self) -> yts_movies::Quality {
+        match self.quality.as_deref() {
+            Some("480p") => yts_movies::Quality::_480p,
+            Some("720p") => yts_movies::Quality::_720p,
+            Some("1080p") => yts_movies::Quality::_1080p,
+            Some("2160p") => yts_movies::Quality::_2160p,
+            Some("3d") => yts_movies::Quality::_3D,
+            _ => yts_movies::Quality::All,
+        }
+    }
+
+    /// Quality YTS can not filter, matched with the torrents of each movie
+    pub fn local_quality(&self) -> Option<&str> {
+        self.quality
+            .as_deref()
+            .filter(|_| matches!(self.quality(), yts_movies::Quality::All))
+    }
+
+    pub fn rating(&self) -> yts_movies::Rating {
+        match self.rating {
+            Some(1) => yts_movies::Rating::One,
+            Some(2) => yts_movies::Rating::Two,
+            Some(3) => yts_movies::Rating::Three,
+            Some(4) => yts_movies::Rating::Four,
+            Some(5) => yts_movies::Rating::Five,
+            Some(6) => yts_movies::Rating::Six,
+            Some(7) => yts_movies::Rating::Seven,
+            Some(8) => yts_movies::Rating::Eight,
+            Some(9) => yts_movies::Rating::Nine,
+            _ => yts_movies::Rating::All,
+        }
+    }
+
+    pub fn order(&self, default: &yts_movies::OrderBy) -> yts_movies::OrderBy {
+        self.order
+            .as_deref()
+            .and_then(|order| yts_movies::OrderBy::try_from(order).ok())
+            .unwrap_or(default.clone())
+    }
+}
+
+/// Allowed values of a filter, empty for free values like year:
+fn values(filter: &str) -> &'static [&'static str] {
+    match filter {
+        "rating" => &RATINGS,
+        "order" => &ORDERS,
+        "genre" => &GENRES,
+        "quality" => &QUALITIES,
+        _ => &[],
+    }
+}
+
+/// Filters or filter values starting with the word.
+/// Ex: `or` gives `order:` and `order:l` gives `order:latest` and `order:likes`
+pub fn completions(word: &str) -> Vec<String> {
+    if word.is_empty() || word.starts_with('"') {
+        return vec![];
+    }
+
+    let word = word.to_lowercase();
+
+    match word.split_once(':') {
+        Some((filter, value)) => values(filter)
+            .iter()
+            .filter(|v| v.starts_with(value))
+            .map(|v| format!("{filter}:{v}"))
+            .collect(),
+        None => FILTERS
+            .iter()
+            .filter(|filter| filter.starts_with(&word))
+            .map(|filter| format!("{filter}:"))
+            .collect(),
+    }
+}
+
+/// Search text with the filter replaced by the value, or removed without one.
+/// Ex: `with_filter("alien genre:drama", "genre", Some("horror"))` is `alien genre:horror`
+pub fn with_filter(text: &str, filter: &str, value: Option<&str>) -> String {
+    let mut words = match tokenize(text) {
+        Ok(tokens) => tokens
+            .iter()
+            .filter(|token| {
+                token.quoted
+                    || !token
+                        .text
+                        .split_once(':')
+                        .is_some_and(|(key, _)| key.eq_ignore_ascii_case(filter))
+            })
+            .map(|token| text[token.span.clone()].to_string())
+            .collect(),
+        Err(_) => vec![text.trim().to_string()],
+    };
+
+    words.retain(|word| !word.is_empty());
+    words.extend(value.map(|value| format!("{filter}:{value}")));
+    words.join(" ")
+}
+
+/// Genre names of YTS ("Sci-Fi") compared with the filter values ("sci-fi")
+pub fn same_genre(genre: &str, filter: &str) -> bool {
+    let normalize = |text: &str| {
+        text.chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_lowercase()
+    };
+
+    normalize(genre) == normalize(filter)
+}
+
+/// YTS genre of a genre name, "Sci-Fi" or "sci-fi". None for genres YTS does not filter
+pub fn yts_genre(name: &str) -> Option<yts_movies::Genre> {
+    use yts_movies::Genre;
+
+    let genre = match *GENRES.iter().find(|genre| same_genre(name, genre))? {
+        "action" => Genre::Action,
+        "adventure" => Genre::Adventure,
+        "animation" => Genre::Animation,
+        "biography" => Genre::Biography,
+        "comedy" => Genre::Comedy,
+        "crime" => Genre::Crime,
+        "documentary" => Genre::Documentary,
+        "drama" => Genre::Drama,
+        "family" => Genre::Family,
+        "fantasy" => Genre::Fantasy,
+        "film-noir" => Genre::FilmNoir,
+        "game-show" => Genre::GameShow,
+        "history" => Genre::History,
+        "horror" => Genre::Horror,
+        "music" => Genre::Music,
+        "musical" => Genre::Musical,
+        "mystery" => Genre::Mystery,
+        "news" => Genre::News,
+        "reality-tv" => Genre::RealityTv,
+        "romance" => Genre::Romance,
+        "sci-fi" => Genre::SciFi,
+        "sport" => Genre::Sport,
+        "talk-show" => Genre::TalkShow,
+        "thriller" => Genre::Thriller,
+        "war" => Genre::War,
+        "western" => Genre::Western,
+        _ => return None,
+    };
+
+    Some(genre)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::elements::{Query, Years, completions, with_filter};
+
+    #[test]
+    fn filters_in_any_position() {
+        let query = Query::parse("year:1982 \"the thing\" genre:Horror order:latest").unwrap();
+
+        assert_eq!(query.title, "the thing");
+        assert_eq!(query.year, Some(Years::single(1982)));
+        assert_eq!(query.genre.as_deref(), Some("horror"));
+        assert_eq!(query.order.as_deref(), Some("latest"));
+
+        let query = Query::parse("Mission: Impossible \"year:2000\"").unwrap();
+        assert_eq!(query.title, "Mission: Impossible year:2000");
+    }
+
+    #[test]
+    fn invalid_tokens_are_reported() {
+        let text = "alien rating:11 order:foo quality:720p";
+        let errors = Query::parse(text).unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(&text[errors[0].span.clone()], "rating:11");
+        assert!(errors[1].message.contains("latest"));
+
+        let errors = Query::parse("\"the thing year:").unwrap_err();
+        assert_eq!(errors[0].message, "missing closing quote");
+        assert!(Query::parse("year:").is_err());
+    }
+
+    #[test]
+    fn complete_filters_and_values() {
+        assert_eq!(completions("or"), vec!["order:"]);
+        assert_eq!(completions("Order:l"), vec!["order:latest", "order:likes"]);
+        assert_eq!(completions("quality:x"), vec!["quality:x265"]);
+        assert_eq!(completions("r").len(), 1);
+        assert!(completions("year:19").is_empty());
+        assert!(completions("matrix").is_empty());
+        assert!(completions("").is_empty());
+    }
+
+    #[test]
+    fn replace_filters() {
+        let text = "\"genre:x\" year:80s Genre:drama";
+        assert_eq!(
+            with_filter(text, "genre", Some("horror")),
+            "\"genre:x\" year:80s genre:horror"
+        );
+        assert_eq!(with_filter(text, "genre", None), "\"genre:x\" year:80s");
+        assert_eq!(with_filter("", "genre", Some("war")), "genre:war");
+    }
+
+    #[test]
+    fn year_ranges_and_decades() {
+        let years = |text: &str| Query::parse(text).unwrap().year.unwrap();
+
+        assert_eq!(
+            years("year:2010-2015"),
+            Years {
+                from: 2010,
+                to: 2015
+            }
+        );
+        assert_eq!(
+            years("year:>2000"),
+            Years {
+                from: 2001,
+                to: 2100
+            }
+        );
+        assert_eq!(
+            years("year:<1970"),
+            Years {
+                from: 1900,
+                to: 1969
+            }
+        );
+        assert_eq!(
+            years("year:80s"),
+            Years {
+                from: 1980,
+                to: 1989
+            }
+        );
+        assert_eq!(
+            years("year:10s"),
+            Years {
+                from: 2010,
+                to: 2019
+            }
+        );
+        assert_eq!(
+            years("year:1920s"),
+            Years {
+                from: 1920,
+                to: 1929
+            }
+        );
+
+        let query = Query::parse("year:80s genre:sci-fi").unwrap();
+        assert!(matches!(query.year(), yts_movies::Year::All));
+        assert!(query.filters_locally());
+        assert!(!Query::parse("year:1999").unwrap().filters_locally());
+
+        let query = Query::parse("genre:sci-fi quality:x265").unwrap();
+        assert!(matches!(query.genre(), yts_movies::Genre::SciFi));
+        assert_eq!(query.local_quality(), Some("x265"));
+        assert!(!Query::parse("quality:1080p").unwrap().filters_locally());
+
+        for invalid in ["year:2015-2010", "year:85s", "year:>x", "year:1800-1900"] {
+            assert!(Query::parse(invalid).is_err(), "{invalid}");
+        }
+    }
+}
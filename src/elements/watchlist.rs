@@ -0,0 +1,374 @@
+use std::{
+    fs::{self, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use ratatui::widgets::{Row, Table, TableState};
+use serde::{Deserialize, Serialize};
+use yts_movies::Movie;
+
+use crate::config::Theme;
+use crate::elements::{Column, Columns, Focus, MovieRecord};
+
+/// Lock file shared by every gativideo using a watchlist, removed when dropped
+pub struct FileLock(PathBuf);
+
+impl FileLock {
+    /// None when another gativideo holds the lock. Locks older than `stale` were left
+    /// by a gativideo which ended before removing them
+    fn acquire(path: PathBuf, stale: Duration) -> io::Result<Option<FileLock>> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let is_stale = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age > stale));
+        if is_stale {
+            let _ = fs::remove_file(&path);
+        }
+
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(_) => Ok(Some(FileLock(path))),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Held during a whole check of the monitor, so two never grab the same torrent
+    pub fn check(watchlist: &Path) -> io::Result<Option<FileLock>> {
+        Self::acquire(
+            watchlist.with_extension("check.lock"),
+            Duration::from_secs(60 * 60),
+        )
+    }
+
+    /// Held while the file is read, changed and written. Saves are short, so it is waited for
+    fn save(watchlist: &Path) -> anyhow::Result<FileLock> {
+        let path = watchlist.with_extension("save.lock");
+        let until = Instant::now() + Duration::from_secs(10);
+
+        loop {
+            if let Some(lock) = Self::acquire(path.clone(), Duration::from_secs(5))? {
+                return Ok(lock);
+            }
+            if Instant::now() >= until {
+                anyhow::bail!("the watchlist is being saved by another gativideo");
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WatchlistEntry {
+    pub movie: Movie,
+    pub note: String,
+    /// Quality and size of the torrent added by the watchlist monitor
+    pub grabbed: Option<String>,
+}
+
+/// Entry as saved in the watchlist file
+#[derive(Serialize, Deserialize)]
+struct SavedEntry {
+    movie: MovieRecord,
+    #[serde(default)]
+    note: String,
+    #[serde(default)]
+    grabbed: Option<String>,
+}
+
+impl From<&WatchlistEntry> for SavedEntry {
+    fn from(entry: &WatchlistEntry) -> Self {
+        Self {
+            movie: MovieRecord::from(&entry.movie),
+            note: entry.note.clone(),
+            grabbed: entry.grabbed.clone(),
+        }
+    }
+}
+
+impl From<SavedEntry> for WatchlistEntry {
+    fn from(entry: SavedEntry) -> Self {
+        Self {
+            movie: Movie::from(entry.movie),
+            note: entry.note,
+            grabbed: entry.grabbed,
+        }
+    }
+}
+
+/// Movies saved for later, with a note each
+pub struct Watchlist {
+    pub entries: Vec<WatchlistEntry>,
+    pub table_state: TableState,
+    /// Whether the view is shown in place of the movies
+    pub show: bool,
+    path: PathBuf,
+}
+
+impl Watchlist {
+    const TITLE: &'static str = " WATCHLIST ";
+
+    const COLUMNS: [Column; 5] = [
+        Column::new("Year", 10, 4, 1),
+        Column::new("Name", 40, 20, 0),
+        Column::new("Note", 30, 15, 2),
+        Column::new("Grabbed", 15, 12, 4),
+        Column::new("Rating", 10, 6, 3),
+    ];
+
+    pub fn new(path: PathBuf) -> Watchlist {
+        let mut table_state = TableState::default();
+        table_state.select_first();
+
+        Self {
+            entries: vec![],
+            table_state,
+            show: false,
+            path,
+        }
+    }
+
+    /// A missing file is an empty watchlist
+    pub fn load(&mut self) -> anyhow::Result<()> {
+        match fs::read(&self.path) {
+            Ok(json) => {
+                let saved: Vec<SavedEntry> = serde_json::from_slice(&json)?;
+                self.entries = saved.into_iter().map(WatchlistEntry::from).collect();
+                Ok(())
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The watchlist of the file as it is now, loaded off the async runtime
+    pub async fn open(path: PathBuf) -> anyhow::Result<Watchlist> {
+        tokio::task::spawn_blocking(move || {
+            let mut watchlist = Watchlist::new(path);
+            watchlist.load()?;
+            Ok(watchlist)
+        })
+        .await?
+    }
+
+    /// Torrents grabbed meanwhile by another gativideo, like a headless watch, are kept
+    /// from the file, the rest of the entries are the ones of this watchlist
+    pub fn save(&self) -> impl Future<Output = anyhow::Result<()>> + Send + 'static {
+        let mut entries = self.entries.clone();
+
+        Self::update(self.path.clone(), move |saved| {
+            for entry in entries.iter_mut().filter(|entry| entry.grabbed.is_none()) {
+                entry.grabbed = saved
+                    .iter()
+                    .find(|saved| saved.movie.link == entry.movie.link)
+                    .and_then(|saved| saved.grabbed.clone());
+            }
+            *saved = entries;
+        })
+    }
+
+    /// Marks the links of the file as grabbed with their torrents, nothing else is changed
+    pub fn save_grabbed(
+        path: PathBuf,
+        grabbed: Vec<(String, String)>,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send + 'static {
+        Self::update(path, move |saved| {
+            for (link, torrent) in grabbed {
+                if let Some(entry) = saved.iter_mut().find(|entry| entry.movie.link == link) {
+                    entry.grabbed = Some(torrent);
+                }
+            }
+        })
+    }
+
+    /// Changes the entries of the file under the save lock. They are written to a
+    /// temporary file which replaces the watchlist, so it is never read half written
+    async fn update(
+        path: PathBuf,
+        change: impl FnOnce(&mut Vec<WatchlistEntry>) + Send + 'static,
+    ) -> anyhow::Result<()> {
+        tokio::task::spawn_blocking(move || {
+            let _lock = FileLock::save(&path)?;
+            let mut watchlist = Watchlist::new(path);
+            watchlist.load()?;
+            change(&mut watchlist.entries);
+
+            let saved = watchlist
+                .entries
+                .iter()
+                .map(SavedEntry::from)
+                .collect::<Vec<_>>();
+            let temporary = watchlist.path.with_extension("json.tmp");
+            fs::write(&temporary, serde_json::to_vec_pretty(&saved)?)?;
+            fs::rename(&temporary, &watchlist.path)?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub fn contains(&self, movie: &Movie) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| entry.movie.link == movie.link)
+    }
+
+    /// Adds a movie at the end. Returns false when it was already there
+    pub fn add(&mut self, movie: &Movie) -> bool {
+        if self.contains(movie) {
+            return false;
+        }
+
+        self.entries.push(WatchlistEntry {
+            movie: movie.clone(),
+            note: String::new(),
+            grabbed: None,
+        });
+        true
+    }
+
+    /// Movies the monitor still looks torrents for
+    pub fn waiting(&self) -> Vec<Movie> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.grabbed.is_none())
+            .map(|entry| entry.movie.clone())
+            .collect()
+    }
+
+    /// Marks the movie of the link as grabbed with a torrent
+    pub fn grabbed(&mut self, link: &str, torrent: &str) {
+        if let Some(entry) = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.movie.link == link)
+        {
+            entry.grabbed = Some(torrent.to_string());
+        }
+    }
+
+    /// Removes an entry, the selection stays within the list
+    pub fn remove(&mut self, index: usize) -> Option<WatchlistEntry> {
+        if index >= self.entries.len() {
+            return None;
+        }
+
+        let entry = self.entries.remove(index);
+        let last = self.entries.len().saturating_sub(1);
+        self.table_state.select(Some(index.min(last)));
+        Some(entry)
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.table_state
+            .selected()
+            .filter(|&selected| selected < self.entries.len())
+    }
+
+    pub fn render(&mut self, focus: &Focus, theme: &Theme, width: u16) -> (Table<'_>, u16) {
+        let columns = Columns::fit(&Self::COLUMNS, width, theme.icons.highlight);
+
+        let rows = self
+            .entries
+            .iter()
+            .map(|entry| {
+                columns.row(vec![
+                    entry.movie.year.to_string(),
+                    entry.movie.name.clone(),
+                    entry.note.clone(),
+                    entry.grabbed.clone().unwrap_or_default(),
+                    entry.movie.rating.to_string(),
+                ])
+            })
+            .collect::<Vec<Row>>();
+
+        let (header, constraint) = if !rows.is_empty() {
+            (
+                columns.header(theme.header_style(), None, &theme.icons),
+                rows.len() as u16 + 4,
+            )
+        } else {
+            (Row::default(), 2)
+        };
+
+        let footer = format!(" {} Movie/s ", self.entries.len());
+
+        (
+            Table::new(rows, columns.widths())
+                .header(header)
+                .block(
+                    theme
+                        .panel_block(matches!(focus, Focus::Watchlist))
+                        .title(Self::TITLE)
+                        .title_style(theme.title_style())
+                        .title_alignment(ratatui::layout::Alignment::Center)
+                        .title_bottom(footer),
+                )
+                .column_spacing(1)
+                .style(theme.text_style())
+                .row_highlight_style(theme.highlight_style())
+                .highlight_symbol(theme.icons.highlight),
+            constraint,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use yts_movies::Movie;
+
+    use crate::elements::Watchlist;
+
+    #[tokio::test]
+    async fn movies_are_added_once_and_saved() {
+        let path = env::temp_dir()
+            .join(format!("gativideo-watchlist-{}", std::process::id()))
+            .join("watchlist.json");
+        let movie = |name: &str| Movie {
+            name: name.to_string(),
+            year: 1979,
+            rating: 8.5,
+            genres: vec![],
+            link: format!("https://yts.lt/movies/{name}"),
+        };
+
+        let mut watchlist = Watchlist::new(path.clone());
+        watchlist.load().unwrap();
+        assert!(watchlist.entries.is_empty());
+
+        assert!(watchlist.add(&movie("alien")));
+        assert!(watchlist.add(&movie("stalker")));
+        assert!(!watchlist.add(&movie("alien")));
+        watchlist.entries[1].note = String::from("Tarkovsky");
+        watchlist.save().await.unwrap();
+
+        // A torrent grabbed by another gativideo is kept when this one saves
+        let mut other = Watchlist::open(path.clone()).await.unwrap();
+        other.grabbed(&movie("alien").link, "1080p 1.9 GB");
+        other.save().await.unwrap();
+        watchlist.save().await.unwrap();
+
+        let mut loaded = Watchlist::open(path.clone()).await.unwrap();
+        assert_eq!(loaded.entries.len(), 2);
+        assert_eq!(loaded.entries[1].note, "Tarkovsky");
+        assert_eq!(loaded.entries[0].grabbed.as_deref(), Some("1080p 1.9 GB"));
+
+        loaded.table_state.select(Some(1));
+        assert!(loaded.remove(1).is_some());
+        assert_eq!(loaded.selected(), Some(0));
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -1,11 +1,23 @@
 mod app;
+mod cli;
 mod config;
 mod downloads;
 mod elements;
+mod tasks;
+
+use clap::Parser;
 
 #[tokio::main]
 async fn main() {
-    if let Err(e) = app::run().await {
+    let cli = cli::Cli::parse();
+
+    let result = match cli.command {
+        Some(command) => cli::run(command, cli.json).await,
+        None => app::run().await,
+    };
+
+    if let Err(e) = result {
         eprintln!("{e}");
+        std::process::exit(1);
     }
 }
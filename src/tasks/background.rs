@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use tokio::{
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+    time::Instant,
+};
+use yts_movies::{Movie, Response, Torrent};
+
+use crate::elements::Details;
+use crate::tasks::Check;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadChange {
+    Add,
+    Toggle,
+    Remove,
+}
+
+pub enum Event {
+    Movies(yts_movies::Result<Response>),
+    /// Results of a search fired while typing
+    LiveMovies(yts_movies::Result<Response>),
+    Torrents(yts_movies::Result<Vec<Torrent>>),
+    Subtitles(opensubs::Result<(opensubs::Page, Vec<opensubs::Subtitle>)>),
+    SubtitleDownloaded(String, anyhow::Result<()>),
+    Downloads(transmission_rpc::types::Result<Vec<transmission_rpc::types::Torrent>>),
+    DownloadChanged(DownloadChange, transmission_rpc::types::Result<bool>),
+    HistorySaved(anyhow::Result<()>),
+    WatchlistSaved(anyhow::Result<()>),
+    /// None when another gativideo was checking
+    WatchlistChecked(anyhow::Result<Option<Vec<(Movie, Check)>>>),
+    /// Files and subtitles of the download dir
+    LibraryScanned(Vec<String>, Vec<String>),
+    /// Details of the movie with the link
+    Details(String, anyhow::Result<Details>),
+}
+
+struct Pending {
+    id: u64,
+    /// None for requests which are not shown as loading
+    label: Option<&'static str>,
+    handle: JoinHandle<()>,
+    started: Instant,
+}
+
+pub struct Background {
+    sender: UnboundedSender<(Option<u64>, Event)>,
+    receiver: UnboundedReceiver<(Option<u64>, Event)>,
+    pending: Option<Pending>,
+    next_id: u64,
+}
+
+impl Background {
+    pub fn new() -> Background {
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        Self {
+            sender,
+            receiver,
+            pending: None,
+            next_id: 0,
+        }
+    }
+
+    /// Spawns a user initiated request. Only one can be pending at a time,
+    /// a new one replaces (and aborts) the previous.
+    pub fn request<F, M>(&mut self, label: &'static str, task: F, into_event: M)
+    where
+        F: Future + Send + 'static,
+        M: FnOnce(F::Output) -> Event + Send + 'static,
+    {
+        self.start(Some(label), task, into_event);
+    }
+
+    /// Like a request, but without the loading popup, so typing is not interrupted
+    pub fn request_quietly<F, M>(&mut self, task: F, into_event: M)
+    where
+        F: Future + Send + 'static,
+        M: FnOnce(F::Output) -> Event + Send + 'static,
+    {
+        self.start(None, task, into_event);
+    }
+
+    fn start<F, M>(&mut self, label: Option<&'static str>, task: F, into_event: M)
+    where
+        F: Future + Send + 'static,
+        M: FnOnce(F::Output) -> Event + Send + 'static,
+    {
+        self.cancel();
+
+        self.next_id += 1;
+        let id = self.next_id;
+        let sender = self.sender.clone();
+
+        let handle = tokio::spawn(async move {
+            let _ = sender.send((Some(id), into_event(task.await)));
+        });
+
+        self.pending = Some(Pending {
+            id,
+            label,
+            handle,
+            started: Instant::now(),
+        });
+    }
+
+    /// Spawns a task which is not tracked as pending and can not be cancelled
+    pub fn spawn<F, M>(&self, task: F, into_event: M)
+    where
+        F: Future + Send + 'static,
+        M: FnOnce(F::Output) -> Event + Send + 'static,
+    {
+        let sender = self.sender.clone();
+
+        tokio::spawn(async move {
+            let _ = sender.send((None, into_event(task.await)));
+        });
+    }
+
+    /// Spawns a task whose result is not needed, like filling a cache
+    pub fn detach<F>(&self, task: F)
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        tokio::spawn(task);
+    }
+
+    pub fn cancel(&mut self) -> bool {
+        match self.pending.take() {
+            Some(pending) => {
+                pending.handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn pending(&self) -> Option<(&'static str, Duration)> {
+        self.pending
+            .as_ref()
+            .and_then(|pending| Some((pending.label?, pending.started.elapsed())))
+    }
+
+    /// Returns the next finished event. Results of cancelled or replaced requests are dropped.
+    pub fn try_recv(&mut self) -> Option<Event> {
+        while let Ok((id, event)) = self.receiver.try_recv() {
+            match id {
+                None => return Some(event),
+                Some(id) if self.pending.as_ref().is_some_and(|p| p.id == id) => {
+                    self.pending = None;
+                    return Some(event);
+                }
+                Some(_) => {}
+            }
+        }
+        None
+    }
+}
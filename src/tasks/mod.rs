@@ -0,0 +1,5 @@
+mod background;
+mod monitor;
+
+pub use background::*;
+pub use monitor::*;
@@ -0,0 +1,269 @@
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use yts_movies::{Movie, Torrent, Yts};
+
+use crate::config::Config;
+use crate::downloads::Transmission;
+use crate::elements::{FileLock, Watchlist, parse_numbers, parse_size};
+
+/// What a torrent needs to be grabbed for a watchlist movie
+#[derive(Debug, Clone, Default)]
+pub struct WatchRules {
+    /// Lowest resolution, 1080 for "1080p"
+    pub min_quality: Option<u32>,
+    /// "x264" or "x265"
+    pub codec: Option<String>,
+    pub min_seeds: u32,
+    /// Bytes
+    pub max_size: Option<f64>,
+}
+
+impl WatchRules {
+    /// Whether a torrent of the quality ("1080p.x265"), size ("1.9 GB") and
+    /// peers/seeds ("12/34") passes the rules
+    pub fn accepts(&self, quality: &str, size: &str, peers_seeds: &str) -> bool {
+        let resolution = parse_numbers(quality).first().copied().unwrap_or_default();
+        let seeds = parse_numbers(peers_seeds)
+            .last()
+            .copied()
+            .unwrap_or_default();
+        // YTS only names the codec of the x265 torrents
+        let codec = if quality.to_lowercase().contains("x265") {
+            "x265"
+        } else {
+            "x264"
+        };
+
+        self.min_quality.is_none_or(|min| resolution >= min as f64)
+            && self.codec.as_deref().is_none_or(|c| c == codec)
+            && seeds >= self.min_seeds as f64
+            && self.max_size.is_none_or(|max| parse_size(size) <= max)
+    }
+
+    /// Highest quality torrent passing the rules, the one with more seeds between equals
+    pub fn best<'t>(&self, torrents: &'t [Torrent]) -> Option<&'t Torrent> {
+        let rank = |torrent: &Torrent| {
+            let quality: &str = (&torrent.quality).into();
+            let resolution = parse_numbers(quality).first().copied().unwrap_or_default();
+            let seeds = parse_numbers(&torrent.peers_seeds)
+                .last()
+                .copied()
+                .unwrap_or_default();
+            (resolution, seeds)
+        };
+
+        torrents
+            .iter()
+            .filter(|torrent| {
+                let quality: &str = (&torrent.quality).into();
+                self.accepts(quality, &torrent.size, &torrent.peers_seeds)
+            })
+            .max_by(|a, b| {
+                rank(a)
+                    .partial_cmp(&rank(b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    }
+}
+
+/// Result of checking a watchlist movie
+#[derive(Debug, Clone, PartialEq)]
+pub enum Check {
+    /// Quality and size of the torrent added to Transmission
+    Grabbed(String),
+    /// Torrents found, none of them passing the rules
+    Waiting(usize),
+    Failed(String),
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Check::Grabbed(torrent) => write!(f, "added {torrent} to Transmission"),
+            Check::Waiting(0) => write!(f, "no torrents yet"),
+            Check::Waiting(torrents) => write!(f, "none of {torrents} torrent/s match the rules"),
+            Check::Failed(error) => write!(f, "failed {error}"),
+        }
+    }
+}
+
+/// Grabs the watchlist movies once a torrent passing the rules shows up in YTS
+pub struct Monitor {
+    rules: WatchRules,
+    transmission: Arc<Transmission>,
+    host: String,
+    watchlist_path: PathBuf,
+    log_path: PathBuf,
+}
+
+impl Monitor {
+    pub fn new(config: &Config) -> anyhow::Result<Monitor> {
+        Ok(Self {
+            rules: config.watch_rules.clone(),
+            transmission: Arc::new(Transmission::new(
+                config.transmission_host.clone(),
+                config.transmission_username.clone(),
+                config.transmission_password.clone(),
+                config.yts_download_dir.clone(),
+            )?),
+            host: config.yts_host.clone(),
+            watchlist_path: config.watchlist_path.clone(),
+            log_path: config.watch_log_path.clone(),
+        })
+    }
+
+    /// Checks the movies waiting in the watchlist file one by one, adding the best torrent
+    /// of each to Transmission. Every check is appended to the log.
+    /// The file is locked meanwhile, so the TUI and a headless watch never grab the same
+    /// torrent, and only the grabbed torrents are written back to it.
+    /// None when another gativideo is checking, the check is skipped
+    pub fn check(
+        &self,
+    ) -> impl Future<Output = anyhow::Result<Option<Vec<(Movie, Check)>>>> + Send + 'static {
+        let rules = self.rules.clone();
+        let transmission = self.transmission.clone();
+        let host = self.host.clone();
+        let watchlist_path = self.watchlist_path.clone();
+        let log_path = self.log_path.clone();
+
+        async move {
+            let lock_path = watchlist_path.clone();
+            let Some(_lock) =
+                tokio::task::spawn_blocking(move || FileLock::check(&lock_path)).await??
+            else {
+                log(
+                    &log_path,
+                    "Skipped, another gativideo is checking the watchlist",
+                );
+                return Ok(None);
+            };
+
+            let yts = Yts::new(&host, Duration::from_secs(30));
+            let mut checks = vec![];
+
+            for movie in Watchlist::open(watchlist_path.clone()).await?.waiting() {
+                let check = match yts.torrents(&movie).await {
+                    Ok(torrents) => match rules.best(&torrents) {
+                        Some(torrent) => {
+                            let quality: &str = (&torrent.quality).into();
+                            match transmission.add(&torrent.link).await {
+                                Ok(true) => Check::Grabbed(format!("{quality} {}", torrent.size)),
+                                Ok(false) => {
+                                    Check::Failed(String::from("Transmission refused the torrent"))
+                                }
+                                Err(e) => Check::Failed(format!("adding torrent {e}")),
+                            }
+                        }
+                        None => Check::Waiting(torrents.len()),
+                    },
+                    Err(e) => Check::Failed(format!("searching torrents {e}")),
+                };
+
+                log(
+                    &log_path,
+                    &format!("{} ({}): {check}", movie.name, movie.year),
+                );
+                checks.push((movie, check));
+            }
+
+            // The notes and movies changed during the checks are kept
+            let grabbed = checks
+                .iter()
+                .filter_map(|(movie, check)| match check {
+                    Check::Grabbed(torrent) => Some((movie.link.clone(), torrent.clone())),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            if !grabbed.is_empty() {
+                Watchlist::save_grabbed(watchlist_path, grabbed).await?;
+            }
+
+            Ok(Some(checks))
+        }
+    }
+}
+
+/// A log which can not be written does not stop the checks
+fn log(path: &Path, line: &str) {
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(file, "{} {line}", timestamp());
+    }
+}
+
+/// UTC date and time, from the civil calendar algorithm of Howard Hinnant
+fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs());
+    let (days, time) = ((secs / 86400) as i64, secs % 86400);
+
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use crate::elements::FileLock;
+    use crate::tasks::WatchRules;
+
+    #[test]
+    fn torrents_pass_every_rule() {
+        let rules = WatchRules {
+            min_quality: Some(1080),
+            codec: Some(String::from("x265")),
+            min_seeds: 10,
+            max_size: Some(4.0 * 1024f64.powi(3)),
+        };
+
+        assert!(rules.accepts("1080p.x265", "1.9 GB", "3/25"));
+        assert!(rules.accepts("2160p.x265", "3.8 GB", "1/10"));
+        assert!(!rules.accepts("720p.x265", "900 MB", "3/25"));
+        assert!(!rules.accepts("1080p", "1.9 GB", "3/25"));
+        assert!(!rules.accepts("1080p.x265", "1.9 GB", "30/9"));
+        assert!(!rules.accepts("2160p.x265", "6.2 GB", "3/25"));
+
+        assert!(WatchRules::default().accepts("3D", "1 GB", "0/0"));
+    }
+
+    #[test]
+    fn one_check_at_a_time() {
+        let path = env::temp_dir()
+            .join(format!("gativideo-monitor-{}", std::process::id()))
+            .join("watchlist.json");
+
+        let lock = FileLock::check(&path).unwrap();
+        assert!(lock.is_some());
+        assert!(FileLock::check(&path).unwrap().is_none());
+        drop(lock);
+        assert!(FileLock::check(&path).unwrap().is_some());
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}